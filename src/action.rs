@@ -0,0 +1,47 @@
+use std::fmt;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Action {
+    WAIT,
+    FASTER, 
+    SLOWER, 
+    PORT, 
+    STARBOARD, 
+    FIRE(i32, i32), 
+    MINE,
+}
+
+impl Action {
+    pub fn parse(line: &str) -> Option<Action> {
+        let inputs = line.split_whitespace().collect::<Vec<_>>();
+        match inputs.first() {
+            Some(&"WAIT") => Some(Action::WAIT),
+            Some(&"FASTER") => Some(Action::FASTER),
+            Some(&"SLOWER") => Some(Action::SLOWER),
+            Some(&"PORT") => Some(Action::PORT),
+            Some(&"STARBOARD") => Some(Action::STARBOARD),
+            Some(&"MINE") => Some(Action::MINE),
+            Some(&"FIRE") if inputs.len() >= 3 => {
+                match (inputs[1].parse::<i32>(), inputs[2].parse::<i32>()) {
+                    (Ok(x), Ok(y)) => Some(Action::FIRE(x, y)),
+                    _ => None,
+                }
+            },
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Action::WAIT => write!(f, "WAIT"),
+            Action::FASTER => write!(f, "FASTER"),
+            Action::SLOWER => write!(f, "SLOWER"),
+            Action::PORT => write!(f, "PORT"),
+            Action::STARBOARD => write!(f, "STARBOARD"),
+            Action::FIRE(x, y) => write!(f, "FIRE {} {} YARRR", x, y),
+            Action::MINE => write!(f, "MINE"),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use action::Action;
+use game::Game;
+use referee;
+use timer::FIRST_TURN_MS;
+
+pub struct Outcome {
+    // 0 or 1, None on a draw.
+    pub winner: Option<usize>,
+    pub turns: i32,
+    pub rum: (i32, i32),
+    // Side that crashed, timed out or answered nonsense, and why. It loses the game.
+    pub failure: Option<(usize, String)>,
+}
+
+// One side of a game: gets the input block of every turn and answers one action per ship.
+pub trait Player {
+    fn turn(&mut self, tick: i32, input: &str, ship_count: usize) -> Result<Vec<Action>, String>;
+}
+
+impl Player for Game {
+    fn turn(&mut self, tick: i32, input: &str, _ship_count: usize) -> Result<Vec<Action>, String> {
+        let mut input = input.as_bytes();
+        let actions = if tick == 0 {self.init(&mut input)} else {self.play(&mut input)};
+        actions.map_err(|e| e.to_string())
+    }
+}
+
+// A bot executable talking over stdin/stdout, with the CodinGame time limits.
+pub struct ProcessBot {
+    pub command: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    timeout: Duration,
+}
+
+impl ProcessBot {
+    // `command` is split on whitespace, so it can carry flags for the bot.
+    pub fn spawn(command: &str, timeout: Duration, verbose: bool) -> Result<ProcessBot, String> {
+        let mut args = command.split_whitespace();
+        let program = args.next().ok_or_else(|| "empty bot command".to_string())?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(if verbose {Stdio::inherit()} else {Stdio::null()})
+            .spawn()
+            .map_err(|e| format!("{}: {}", command, e))?;
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                match line {
+                    Ok(line) => if tx.send(line).is_err() {
+                        break;
+                    },
+                    Err(_) => break,
+                }
+            }
+        });
+        Ok(ProcessBot {
+            command: command.to_string(),
+            child: child,
+            stdin: stdin,
+            lines: rx,
+            timeout: timeout,
+        })
+    }
+}
+
+impl Player for ProcessBot {
+    fn turn(&mut self, tick: i32, input: &str, ship_count: usize) -> Result<Vec<Action>, String> {
+        let timeout = if tick == 0 {self.timeout.max(Duration::from_millis(FIRST_TURN_MS))} else {self.timeout};
+        self.stdin.write_all(input.as_bytes())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| format!("cannot write input: {}", e))?;
+        let mut actions = Vec::new();
+        for _ in 0..ship_count {
+            let line = self.lines.recv_timeout(timeout)
+                .map_err(|_| format!("no answer within {}ms", timeout.as_millis()))?;
+            let action = Action::parse(&line).ok_or_else(|| format!("invalid action '{}'", line))?;
+            actions.push(action);
+        }
+        Ok(actions)
+    }
+}
+
+impl Drop for ProcessBot {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// One game on the map of `seed`, player 0 starting at the top.
+pub fn play_players(seed: u64, players: &mut [&mut dyn Player; 2]) -> Outcome {
+    let mut game = referee::generate(seed);
+    let mut failure = None;
+    while !referee::is_over(&game) {
+        let mut actions = HashMap::new();
+        for (player, bot) in players.iter_mut().enumerate() {
+            let ids = referee::ship_ids(&game, player as i32);
+            let input = referee::player_input(&game, player as i32);
+            match bot.turn(game.current_tick, &input, ids.len()) {
+                Ok(bot_actions) => actions.extend(ids.into_iter().zip(bot_actions)),
+                Err(e) => {
+                    failure = Some((player, e));
+                    break;
+                },
+            }
+        }
+        if failure.is_some() {
+            break;
+        }
+        game = referee::next_turn(&game, &actions);
+    }
+    let rum = referee::total_rum(&game);
+    let winner = match failure {
+        Some((player, _)) => Some(1 - player),
+        None if rum.0 > rum.1 => Some(0),
+        None if rum.1 > rum.0 => Some(1),
+        None => None,
+    };
+    Outcome {
+        winner: winner,
+        turns: game.current_tick,
+        rum: rum,
+        failure: failure,
+    }
+}
+
+// One game between two configured bots in this process. `bots` are fresh `Game`s carrying
+// the settings (params, planner) of each side.
+pub fn play(seed: u64, bots: &[Game; 2]) -> Outcome {
+    let (mut a, mut b) = (bots[0].clone(), bots[1].clone());
+    play_players(seed, &mut [&mut a, &mut b])
+}
+
+// Runs `job(0..count)` on `threads` threads and returns the results in job order.
+pub fn run_parallel<T: Send, F: Fn(usize) -> T + Sync>(count: usize, threads: usize, job: F) -> Vec<T> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..count).map(|_| None).collect::<Vec<Option<T>>>());
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                if i >= count {
+                    break;
+                }
+                let result = job(i);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+    results.into_inner().unwrap().into_iter().map(|result| result.unwrap()).collect()
+}
+
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+// Score of a side over a set of games, draws count half, with the half width of its 95%
+// confidence interval.
+pub fn win_rate(scores: &[f64]) -> (f64, f64) {
+    let n = scores.len() as f64;
+    if n == 0f64 {
+        return (0.5, 0.5);
+    }
+    let mean = scores.iter().sum::<f64>()/n;
+    let variance = scores.iter().map(|s| (s - mean)*(s - mean)).sum::<f64>()/n;
+    (mean, 1.96*(variance/n).sqrt())
+}
@@ -0,0 +1,677 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+const USAGE: &str = "usage: bundle [--crate-dir DIR] [--bin FILE] [-o OUTPUT]";
+
+// Copy of `text` with comments and the contents of string and char literals blanked out,
+// so that keywords and brackets can be searched by byte position without a parser.
+fn mask(text: &str) -> Vec<u8> {
+    let src = text.as_bytes();
+    let mut out = src.to_vec();
+    let mut i = 0;
+    let blank = |out: &mut Vec<u8>, from: usize, to: usize| {
+        for b in out[from..to].iter_mut() {
+            if *b != b'\n' {
+                *b = b' ';
+            }
+        }
+    };
+    while i < src.len() {
+        if src[i..].starts_with(b"//") {
+            let end = src[i..].iter().position(|&b| b == b'\n').map(|p| i + p).unwrap_or(src.len());
+            blank(&mut out, i, end);
+            i = end;
+        } else if src[i..].starts_with(b"/*") {
+            let mut depth = 0;
+            let mut j = i;
+            while j < src.len() {
+                if src[j..].starts_with(b"/*") {
+                    depth += 1;
+                    j += 2;
+                } else if src[j..].starts_with(b"*/") {
+                    depth -= 1;
+                    j += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    j += 1;
+                }
+            }
+            blank(&mut out, i, j);
+            i = j;
+        } else if (src[i] == b'r') && !is_ident(src, i) && raw_string_hashes(src, i + 1).is_some() {
+            let hashes = raw_string_hashes(src, i + 1).unwrap();
+            let start = i + 2 + hashes;
+            let mut j = start;
+            while j < src.len() {
+                if (src[j] == b'"') && src[j + 1..].iter().take(hashes).filter(|&&b| b == b'#').count() == hashes {
+                    break;
+                }
+                j += 1;
+            }
+            blank(&mut out, start, j);
+            i = j + 1 + hashes;
+        } else if src[i] == b'"' {
+            let mut j = i + 1;
+            while (j < src.len()) && (src[j] != b'"') {
+                j += if src[j] == b'\\' {2} else {1};
+            }
+            blank(&mut out, i + 1, j);
+            i = j + 1;
+        } else if src[i] == b'\'' {
+            let end = if src.get(i + 1) == Some(&b'\\') {
+                src[i + 2..].iter().position(|&b| b == b'\'').map(|p| i + 2 + p)
+            } else {
+                // A char literal closes after one (possibly multi-byte) char, a lifetime does not.
+                let width = text[i + 1..].chars().next().map(|c| c.len_utf8()).unwrap_or(1);
+                if src.get(i + 1 + width) == Some(&b'\'') {Some(i + 1 + width)} else {None}
+            };
+            match end {
+                Some(end) => {
+                    blank(&mut out, i + 1, end);
+                    i = end + 1;
+                },
+                None => i += 1,
+            }
+        } else {
+            i += 1;
+        }
+    }
+    out
+}
+
+fn raw_string_hashes(src: &[u8], from: usize) -> Option<usize> {
+    let hashes = src[from..].iter().take_while(|&&b| b == b'#').count();
+    if src.get(from + hashes) == Some(&b'"') {Some(hashes)} else {None}
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || (b == b'_')
+}
+
+// True when the byte before `i` continues an identifier (so `i` is not a token start).
+fn is_ident(src: &[u8], i: usize) -> bool {
+    (i > 0) && is_ident_byte(src[i - 1])
+}
+
+fn skip_ws(src: &[u8], mut i: usize) -> usize {
+    while (i < src.len()) && src[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+fn keyword_at(src: &[u8], i: usize, word: &str) -> bool {
+    let word = word.as_bytes();
+    src[i..].starts_with(word) && !is_ident(src, i) &&
+        !src.get(i + word.len()).map(|&b| is_ident_byte(b)).unwrap_or(false)
+}
+
+fn read_ident(src: &[u8], i: usize) -> Option<(String, usize)> {
+    let len = src[i..].iter().take_while(|&&b| is_ident_byte(b)).count();
+    if len == 0 {
+        return None;
+    }
+    Some((String::from_utf8_lossy(&src[i..i + len]).into_owned(), i + len))
+}
+
+// Position right after the `]` closing the bracket opened at `i`.
+fn closing_bracket(src: &[u8], i: usize) -> usize {
+    let mut depth = 0;
+    for (j, &b) in src.iter().enumerate().skip(i) {
+        match b {
+            b'[' => depth += 1,
+            b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return j + 1;
+                }
+            },
+            _ => {},
+        }
+    }
+    src.len()
+}
+
+// End of the item starting at `i`: after its first `;` or its first balanced `{}` block.
+fn item_end(src: &[u8], i: usize) -> usize {
+    let mut depth = 0;
+    let mut j = i;
+    while j < src.len() {
+        match src[j] {
+            b'(' | b'[' => depth += 1,
+            b')' | b']' => depth -= 1,
+            b';' if depth == 0 => return j + 1,
+            b'{' if depth == 0 => {
+                let mut braces = 0;
+                while j < src.len() {
+                    match src[j] {
+                        b'{' => braces += 1,
+                        b'}' => {
+                            braces -= 1;
+                            if braces == 0 {
+                                return j + 1;
+                            }
+                        },
+                        _ => {},
+                    }
+                    j += 1;
+                }
+                return src.len();
+            },
+            _ => {},
+        }
+        j += 1;
+    }
+    src.len()
+}
+
+// Outer attributes written right before position `i`, as (start, end) ranges.
+fn attributes_before(src: &[u8], i: usize) -> Vec<(usize, usize)> {
+    let mut attrs = Vec::new();
+    let mut j = i;
+    loop {
+        while (j > 0) && src[j - 1].is_ascii_whitespace() {
+            j -= 1;
+        }
+        if (j == 0) || (src[j - 1] != b']') {
+            break;
+        }
+        let mut depth = 0;
+        let mut k = j;
+        while k > 0 {
+            k -= 1;
+            match src[k] {
+                b']' => depth += 1,
+                b'[' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                },
+                _ => {},
+            }
+        }
+        if (k == 0) || (src[k - 1] != b'#') {
+            break;
+        }
+        attrs.push((k - 1, j));
+        j = k - 1;
+    }
+    attrs.reverse();
+    attrs
+}
+
+fn is_test_attribute(attr: &str) -> bool {
+    let compact: String = attr.chars().filter(|c| !c.is_whitespace()).collect();
+    (compact == "#[test]") || (compact == "#[cfg(test)]")
+}
+
+fn path_attribute(attr: &str) -> Option<String> {
+    let compact: String = attr.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.starts_with("#[path=\"") && compact.ends_with("\"]") {
+        Some(compact[8..compact.len() - 2].to_string())
+    } else {
+        None
+    }
+}
+
+fn strip_test_items(text: &str) -> String {
+    let masked = mask(text);
+    let mut removed: Vec<(usize, usize)> = Vec::new();
+    let mut i = 0;
+    while i < masked.len() {
+        if (masked[i] == b'#') && (masked.get(i + 1) == Some(&b'[')) {
+            let attr_end = closing_bracket(&masked, i + 1);
+            if is_test_attribute(&text[i..attr_end]) {
+                // Skip the remaining attributes of the item before measuring the item itself.
+                let mut j = skip_ws(&masked, attr_end);
+                while (masked.get(j) == Some(&b'#')) && (masked.get(j + 1) == Some(&b'[')) {
+                    j = skip_ws(&masked, closing_bracket(&masked, j + 1));
+                }
+                let start = attributes_before(&masked, i).first().map(|&(s, _)| s).unwrap_or(i);
+                let end = item_end(&masked, j);
+                removed.push((start, end));
+                i = end;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    let mut result = String::new();
+    let mut last = 0;
+    for (start, end) in removed {
+        result.push_str(&text[last..start]);
+        last = end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+    let mut text = String::new();
+    File::open(path)
+        .and_then(|mut f| f.read_to_string(&mut text))
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(text)
+}
+
+fn module_file(file: &Path, name: &str, path_attr: Option<String>) -> Result<PathBuf, String> {
+    let dir = file.parent().unwrap_or_else(|| Path::new("."));
+    if let Some(path) = path_attr {
+        return Ok(dir.join(path));
+    }
+    let stem = file.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+    let base = if (stem == "lib") || (stem == "main") || (stem == "mod") {
+        dir.to_path_buf()
+    } else {
+        dir.join(stem)
+    };
+    let flat = base.join(format!("{}.rs", name));
+    if flat.exists() {
+        return Ok(flat);
+    }
+    let nested = base.join(name).join("mod.rs");
+    if nested.exists() {
+        return Ok(nested);
+    }
+    Err(format!("{}: cannot find module `{}`", file.display(), name))
+}
+
+// Start of the `pub` or `pub(...)` written before position `i`, or `i` itself.
+fn visibility_start(src: &[u8], i: usize) -> usize {
+    let mut j = i;
+    while (j > 0) && src[j - 1].is_ascii_whitespace() {
+        j -= 1;
+    }
+    if (j > 0) && (src[j - 1] == b')') {
+        match src[..j].iter().rposition(|&b| b == b'(') {
+            Some(open) => j = open,
+            None => return i,
+        }
+        while (j > 0) && src[j - 1].is_ascii_whitespace() {
+            j -= 1;
+        }
+    }
+    if (j >= 3) && keyword_at(src, j - 3, "pub") {j - 3} else {i}
+}
+
+// Source of `file` with test-only items removed and every `mod name;` replaced by the
+// module body, recursively.
+fn inline_modules(file: &Path) -> Result<String, String> {
+    let text = strip_test_items(&read_file(file)?);
+    let masked = mask(&text);
+    let mut parts: Vec<(usize, usize, String)> = Vec::new();
+    let mut i = 0;
+    while i < masked.len() {
+        if !keyword_at(&masked, i, "mod") {
+            i += 1;
+            continue;
+        }
+        let declaration = read_ident(&masked, skip_ws(&masked, i + 3))
+            .and_then(|(name, j)| {
+                let j = skip_ws(&masked, j);
+                if masked.get(j) == Some(&b';') {Some((name, j + 1))} else {None}
+            });
+        let (name, end) = match declaration {
+            Some(declaration) => declaration,
+            None => {
+                i += 3;
+                continue;
+            },
+        };
+        let start = visibility_start(&masked, i);
+        let mut path_attr = None;
+        for (attr_start, attr_end) in attributes_before(&masked, start) {
+            if let Some(path) = path_attribute(&text[attr_start..attr_end]) {
+                path_attr = Some(path);
+                parts.push((attr_start, attr_end, String::new()));
+            }
+        }
+        let body = inline_modules(&module_file(file, &name, path_attr)?)?;
+        parts.push((start, end, format!("{}mod {} {{\n{}\n}}", &text[start..i], name, body.trim_end())));
+        i = end;
+    }
+    parts.sort_by_key(|&(start, _, _)| start);
+    let mut result = String::new();
+    let mut last = 0;
+    for (start, end, replacement) in parts {
+        result.push_str(&text[last..start]);
+        result.push_str(&replacement);
+        last = end;
+    }
+    result.push_str(&text[last..]);
+    Ok(result)
+}
+
+// Splits the leading `#![...]` attributes off `text`, they have to open the bundled file.
+fn split_inner_attributes(text: &str) -> (Vec<String>, String) {
+    let masked = mask(text);
+    let mut attrs = Vec::new();
+    let mut i = skip_ws(&masked, 0);
+    while masked[i..].starts_with(b"#![") {
+        let end = closing_bracket(&masked, i + 2);
+        attrs.push(text[i..end].to_string());
+        i = skip_ws(&masked, end);
+    }
+    (attrs, text[i..].to_string())
+}
+
+// Names of the modules declared by `mod name;` in `text`.
+fn declared_modules(text: &str) -> Vec<String> {
+    let masked = mask(text);
+    let mut names = Vec::new();
+    for i in 0..masked.len() {
+        if !keyword_at(&masked, i, "mod") {
+            continue;
+        }
+        if let Some((name, j)) = read_ident(&masked, skip_ws(&masked, i + 3)) {
+            if masked.get(skip_ws(&masked, j)) == Some(&b';') {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+// Prefixes with `crate::` the `use` paths starting at one of `modules`. The sources rely on
+// edition 2015 resolving them from the crate root, the prefix keeps the bundle compiling
+// whichever edition the judge uses.
+fn qualify_crate_paths(text: &str, modules: &[String]) -> String {
+    let masked = mask(text);
+    let mut parts: Vec<(usize, usize)> = Vec::new();
+    for i in 0..masked.len() {
+        if !keyword_at(&masked, i, "use") {
+            continue;
+        }
+        let start = skip_ws(&masked, i + 3);
+        let ident = if masked[start..].starts_with(b"::") {start + 2} else {start};
+        if let Some((name, _)) = read_ident(&masked, ident) {
+            if modules.contains(&name) {
+                parts.push((start, ident));
+            }
+        }
+    }
+    let mut result = String::new();
+    let mut last = 0;
+    for (start, end) in parts {
+        result.push_str(&text[last..start]);
+        result.push_str("crate::");
+        last = end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+// Turns the binary source into a module of the bundle: the `extern crate` of the library
+// goes away and paths through it become `crate::` paths.
+fn adapt_binary(text: &str, crate_name: &str) -> String {
+    let masked = mask(text);
+    let mut parts: Vec<(usize, usize, &str)> = Vec::new();
+    let mut i = 0;
+    while i < masked.len() {
+        if keyword_at(&masked, i, "extern") {
+            let j = skip_ws(&masked, i + 6);
+            if keyword_at(&masked, j, "crate") {
+                let k = skip_ws(&masked, j + 5);
+                if keyword_at(&masked, k, crate_name) {
+                    let start = attributes_before(&masked, i).first().map(|&(s, _)| s).unwrap_or(i);
+                    let end = item_end(&masked, k);
+                    parts.push((start, end, ""));
+                    i = end;
+                    continue;
+                }
+            }
+        }
+        if keyword_at(&masked, i, crate_name) && masked[i + crate_name.len()..].starts_with(b"::") &&
+            !((i > 0) && (masked[i - 1] == b':')) {
+            parts.push((i, i + crate_name.len() + 2, "crate::"));
+            i += crate_name.len() + 2;
+            continue;
+        }
+        if keyword_at(&masked, i, "fn") {
+            let j = skip_ws(&masked, i + 2);
+            if keyword_at(&masked, j, "main") && !text[..i].trim_end().ends_with("pub") {
+                parts.push((i, i, "pub "));
+            }
+        }
+        i += 1;
+    }
+    let mut result = String::new();
+    let mut last = 0;
+    for (start, end, replacement) in parts {
+        result.push_str(&text[last..start]);
+        result.push_str(replacement);
+        last = end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+fn crate_name(crate_dir: &Path) -> Result<String, String> {
+    let manifest = read_file(&crate_dir.join("Cargo.toml"))?;
+    let mut section = String::new();
+    let mut package = None;
+    let mut lib = None;
+    for line in manifest.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            section = line.to_string();
+            continue;
+        }
+        let mut kv = line.splitn(2, '=');
+        let (key, value) = match (kv.next(), kv.next()) {
+            (Some(key), Some(value)) => (key.trim(), value.trim().trim_matches('"').to_string()),
+            _ => continue,
+        };
+        if key != "name" {
+            continue;
+        }
+        if section == "[package]" {
+            package = Some(value);
+        } else if section == "[lib]" {
+            lib = Some(value);
+        }
+    }
+    lib.or(package)
+        .map(|name| name.replace('-', "_"))
+        .ok_or_else(|| "Cargo.toml has no package name".to_string())
+}
+
+fn bundle(crate_dir: &Path, bin: &Path) -> Result<String, String> {
+    let name = crate_name(crate_dir)?;
+    let lib = crate_dir.join("src").join("lib.rs");
+    let modules = declared_modules(&strip_test_items(&read_file(&lib)?));
+    let (mut attrs, library) = split_inner_attributes(&qualify_crate_paths(&inline_modules(&lib)?, &modules));
+    let (bin_attrs, binary) = split_inner_attributes(&adapt_binary(&inline_modules(bin)?, &name));
+    attrs.extend(bin_attrs);
+    let mut out = String::new();
+    out.push_str("// Generated by `bundle`, edit the crate sources instead.\n");
+    for attr in attrs {
+        out.push_str(&attr);
+        out.push('\n');
+    }
+    out.push('\n');
+    out.push_str(library.trim());
+    out.push_str("\n\nmod bin {\n");
+    out.push_str(binary.trim());
+    out.push_str("\n}\n\nfn main() {\n    bin::main();\n}\n");
+    Ok(out)
+}
+
+fn main() {
+    let mut crate_dir = PathBuf::from(".");
+    let mut bin = None;
+    let mut output = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--crate-dir" => crate_dir = args.next().map(PathBuf::from).unwrap_or_else(|| usage()),
+            "--bin" => bin = Some(args.next().map(PathBuf::from).unwrap_or_else(|| usage())),
+            "-o" => output = Some(args.next().map(PathBuf::from).unwrap_or_else(|| usage())),
+            _ => usage(),
+        }
+    }
+    let bin = bin.unwrap_or_else(|| crate_dir.join("src").join("main.rs"));
+    let source = match bundle(&crate_dir, &bin) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        },
+    };
+    let written = match output {
+        Some(path) => File::create(&path).and_then(|mut f| f.write_all(source.as_bytes())),
+        None => io::stdout().write_all(source.as_bytes()),
+    };
+    if let Err(e) = written {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn masked(text: &str) -> String {
+        String::from_utf8(mask(text)).unwrap()
+    }
+
+    // Fresh directory under the system temp dir holding `files`.
+    fn crate_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = env::temp_dir().join(format!("bundle-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        for &(path, text) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, text).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn mask_blanks_comments_and_strings() {
+        let text = "let s = \"mod a; \\\" }\"; // mod b;\n/* mod /* c */ d; */ x";
+        let out = masked(text);
+        assert_eq!(out.len(), text.len());
+        assert!(!out.contains("mod"));
+        assert!(out.starts_with("let s = \""));
+        assert!(out.contains('\n'));
+        assert!(out.ends_with(" x"));
+    }
+
+    #[test]
+    fn mask_blanks_raw_strings() {
+        let text = "let s = r#\"mod a; \"quoted\" }\"#; mod b;";
+        let out = masked(text);
+        assert_eq!(out.matches("mod").count(), 1);
+        assert!(out.ends_with("\"#; mod b;"));
+    }
+
+    #[test]
+    fn mask_tells_char_literals_from_lifetimes() {
+        let text = "fn f<'a>(s: &'a str) -> [char; 3] { ['}', '\\'', 'é'] }";
+        let out = masked(text);
+        assert!(out.contains("fn f<'a>(s: &'a str)"));
+        assert_eq!(out.matches('}').count(), 1);
+        assert!(!out.contains('é'));
+    }
+
+    #[test]
+    fn strip_test_items_removes_test_code_only() {
+        let text = "fn kept() {}\n\
+                    #[cfg(test)]\n\
+                    mod tests {\n    #[test]\n    fn inner() { let _ = \"}\"; }\n}\n\
+                    /// Doc.\n#[test]\n#[ignore]\nfn alone() {}\n\
+                    #[cfg(test)]\nuse std::fmt;\n\
+                    const S: &str = \"#[cfg(test)] mod x {}\";\n";
+        let out = strip_test_items(text);
+        assert!(out.contains("fn kept() {}"));
+        assert!(out.contains("const S: &str = \"#[cfg(test)] mod x {}\";"));
+        assert!(!out.contains("tests"));
+        assert!(!out.contains("alone"));
+        assert!(!out.contains("#[ignore]"));
+        assert!(!out.contains("use std::fmt"));
+    }
+
+    #[test]
+    fn qualify_crate_paths_prefixes_library_modules_only() {
+        let modules = vec!["game".to_string(), "hex".to_string()];
+        let text = "use game::Game;\nuse ::hex::{self, Point};\nuse std::fmt;\nuse gamer::X;\n// use hex::Y;\n";
+        assert_eq!(qualify_crate_paths(text, &modules),
+                   "use crate::game::Game;\nuse crate::hex::{self, Point};\nuse std::fmt;\nuse gamer::X;\n// use hex::Y;\n");
+    }
+
+    #[test]
+    fn adapt_binary_drops_the_library_crate() {
+        let text = "extern crate lib;\n\nuse lib::game::Game;\n\nfn main() {\n    lib::run();\n}\n";
+        assert_eq!(adapt_binary(text, "lib"), "\n\nuse crate::game::Game;\n\npub fn main() {\n    crate::run();\n}\n");
+    }
+
+    #[test]
+    fn bundle_compiles_under_every_edition() {
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let source = bundle(&root, &root.join("src").join("main.rs")).unwrap();
+        let dir = crate_dir("editions", &[("submission.rs", &source)]);
+        for edition in ["2015", "2018", "2021"].iter() {
+            let status = process::Command::new("rustc")
+                .args(["--edition", edition, "--emit=metadata", "--out-dir"])
+                .arg(&dir)
+                .arg(dir.join("submission.rs"))
+                .status()
+                .unwrap();
+            assert!(status.success(), "edition {}", edition);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn module_file_follows_the_crate_layout() {
+        let dir = crate_dir("layout", &[
+            ("src/lib.rs", ""),
+            ("src/flat.rs", ""),
+            ("src/flat/child.rs", ""),
+            ("src/nested/mod.rs", ""),
+            ("src/nested/inner.rs", ""),
+            ("src/elsewhere/impl.rs", ""),
+        ]);
+        let src = dir.join("src");
+        assert_eq!(module_file(&src.join("lib.rs"), "flat", None), Ok(src.join("flat.rs")));
+        assert_eq!(module_file(&src.join("lib.rs"), "nested", None), Ok(src.join("nested").join("mod.rs")));
+        assert_eq!(module_file(&src.join("flat.rs"), "child", None), Ok(src.join("flat").join("child.rs")));
+        assert_eq!(module_file(&src.join("nested").join("mod.rs"), "inner", None), Ok(src.join("nested").join("inner.rs")));
+        assert_eq!(module_file(&src.join("lib.rs"), "renamed", Some("elsewhere/impl.rs".to_string())),
+                   Ok(src.join("elsewhere/impl.rs")));
+        assert!(module_file(&src.join("lib.rs"), "missing", None).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn inline_modules_replaces_declarations() {
+        let dir = crate_dir("inline", &[
+            ("src/lib.rs", "pub mod a;\n#[path = \"other/b_impl.rs\"]\nmod b;\n"),
+            ("src/a/mod.rs", "pub(crate) mod c;\n#[cfg(test)]\nmod tests;\n"),
+            ("src/a/c.rs", "pub fn c() {}\n"),
+            ("src/other/b_impl.rs", "fn b() {}\n"),
+        ]);
+        let out = inline_modules(&dir.join("src").join("lib.rs")).unwrap();
+        assert!(out.contains("pub mod a {\npub(crate) mod c {\npub fn c() {}\n}\n}"));
+        assert!(out.contains("mod b {\nfn b() {}\n}"));
+        assert!(!out.contains("#[path"));
+        assert!(!out.contains("tests"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
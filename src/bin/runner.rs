@@ -0,0 +1,58 @@
+extern crate caribbean;
+
+use std::env;
+use std::process;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use caribbean::arena::{self, ProcessBot};
+use caribbean::timer::TURN_MS;
+
+const USAGE: &str = "usage: runner [--seed N] [--timeout MS] [--verbose] <bot_a> <bot_b>";
+
+fn main() {
+    let mut seed = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let mut timeout = TURN_MS;
+    let mut verbose = false;
+    let mut commands = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--seed" => seed = args.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage()),
+            "--timeout" => timeout = args.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage()),
+            "--verbose" => verbose = true,
+            _ => commands.push(arg),
+        }
+    }
+    if commands.len() != 2 {
+        usage();
+    }
+
+    let mut bots = Vec::new();
+    for command in commands.iter() {
+        match ProcessBot::spawn(command, Duration::from_millis(timeout), verbose) {
+            Ok(bot) => bots.push(bot),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(2);
+            },
+        }
+    }
+
+    println!("seed: {}", seed);
+    let (a, b) = bots.split_at_mut(1);
+    let outcome = arena::play_players(seed, &mut [&mut a[0], &mut b[0]]);
+    if let Some((player, ref e)) = outcome.failure {
+        println!("{} failed on turn {}: {}", commands[player], outcome.turns, e);
+    }
+    match outcome.winner {
+        Some(player) => println!("winner: {} ({})", ["bot_a", "bot_b"][player], commands[player]),
+        None => println!("winner: draw"),
+    }
+    println!("turns: {}", outcome.turns);
+    println!("rum: {} {}", outcome.rum.0, outcome.rum.1);
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
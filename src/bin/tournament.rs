@@ -0,0 +1,229 @@
+extern crate caribbean;
+
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use caribbean::arena::{self, Outcome, Player, ProcessBot};
+use caribbean::planner::PlannerKind;
+use caribbean::strategy::StrategyKind;
+use caribbean::timer::TURN_MS;
+use caribbean::Game;
+
+const USAGE: &str = "usage: tournament [--games N] [--seed N] [--threads N] [--timeout MS] <bot>...
+  a bot is an executable (with its flags, quoted), or `self[:key=value,...]` for this build
+  run in-process, keys: strategy=heuristic|search, planner=tree|beam|mcts, params=FILE, or any EvalParams weight";
+
+const ELO_BASE: f64 = 1500f64;
+
+enum Entrant {
+    Process(String),
+    InProcess(Box<Game>),
+}
+
+impl Entrant {
+    fn parse(spec: &str) -> Result<Entrant, String> {
+        if spec != "self" && !spec.starts_with("self:") {
+            return Ok(Entrant::Process(spec.to_string()));
+        }
+        let mut game = Game::default();
+        for option in spec.trim_start_matches("self").trim_start_matches(':').split(',').filter(|o| !o.is_empty()) {
+            let mut parts = option.splitn(2, '=');
+            let (key, value) = (parts.next().unwrap(), parts.next().unwrap_or(""));
+            match key {
+                "strategy" => game.strategy = StrategyKind::parse(value).ok_or_else(|| format!("unknown strategy '{}'", value))?,
+                "planner" => game.planner.kind = PlannerKind::parse(value).ok_or_else(|| format!("unknown planner '{}'", value))?,
+                "params" => {
+                    let text = fs::read_to_string(value).map_err(|e| format!("{}: {}", value, e))?;
+                    game.params.parse(&text).map_err(|e| format!("{}: {}", value, e))?;
+                },
+                _ => game.params.set(key, value)?,
+            }
+        }
+        Ok(Entrant::InProcess(Box::new(game)))
+    }
+}
+
+// The game between the two entrants of `pair`, or why one of them could not be started.
+fn play(entrants: &[Entrant], pair: (usize, usize), seed: u64, timeout: Duration) -> Result<Outcome, String> {
+    let mut players: Vec<Box<dyn Player>> = Vec::new();
+    for &i in [pair.0, pair.1].iter() {
+        match entrants[i] {
+            Entrant::InProcess(ref game) => players.push(Box::new((**game).clone())),
+            Entrant::Process(ref command) => players.push(Box::new(ProcessBot::spawn(command, timeout, false)?)),
+        }
+    }
+    let (a, b) = players.split_at_mut(1);
+    Ok(arena::play_players(seed, &mut [a[0].as_mut(), b[0].as_mut()]))
+}
+
+// Bradley-Terry strengths fitted by minorization-maximization, as Elo around ELO_BASE. Every
+// pair gets one virtual draw so a bot without a win still has a finite rating.
+fn elo(scores: &[Vec<f64>], games: &[Vec<f64>]) -> Vec<f64> {
+    let n = scores.len();
+    let mut strength = vec![1f64; n];
+    for _ in 0..1000 {
+        let mut next = vec![0f64; n];
+        for i in 0..n {
+            let mut wins = 0f64;
+            let mut denominator = 0f64;
+            for j in 0..n {
+                if i == j {
+                    continue;
+                }
+                wins += scores[i][j] + 0.5;
+                denominator += (games[i][j] + 1f64)/(strength[i] + strength[j]);
+            }
+            next[i] = wins/denominator;
+        }
+        let mean = next.iter().map(|s| s.ln()).sum::<f64>()/(n as f64);
+        strength = next.iter().map(|s| (s.ln() - mean).exp()).collect();
+    }
+    strength.iter().map(|s| ELO_BASE + 400f64*s.log10()).collect()
+}
+
+fn main() {
+    let mut games = 10;
+    let mut seed = 1;
+    let mut threads = arena::default_threads();
+    let mut timeout = TURN_MS;
+    let mut specs = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_ref() {
+            "--games" => games = args.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage()),
+            "--seed" => seed = args.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage()),
+            "--threads" => threads = args.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage()),
+            "--timeout" => timeout = args.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage()),
+            _ => specs.push(arg),
+        }
+    }
+    if specs.len() < 2 {
+        usage();
+    }
+    let entrants: Vec<Entrant> = specs.iter().map(|spec| Entrant::parse(spec).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
+    })).collect();
+    caribbean::LOG.store(false, Ordering::Relaxed);
+
+    // Every pair plays every seed from both sides.
+    let mut jobs = Vec::new();
+    for i in 0..entrants.len() {
+        for j in (i + 1)..entrants.len() {
+            for g in 0..games {
+                jobs.push(((i, j), seed + g as u64));
+                jobs.push(((j, i), seed + g as u64));
+            }
+        }
+    }
+    let timeout = Duration::from_millis(timeout);
+    let results = arena::run_parallel(jobs.len(), threads, |k| play(&entrants, jobs[k].0, jobs[k].1, timeout));
+    // Reported once every thread is done, so no game is cut off halfway.
+    let mut outcomes = Vec::new();
+    for result in results {
+        match result {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(2);
+            },
+        }
+    }
+
+    let n = entrants.len();
+    let mut scores = vec![vec![0f64; n]; n];
+    let mut played = vec![vec![0f64; n]; n];
+    for (&((a, b), game_seed), outcome) in jobs.iter().zip(outcomes.iter()) {
+        if let Some((side, ref e)) = outcome.failure {
+            println!("{} failed on seed {}, turn {}: {}", specs[[a, b][side]], game_seed, outcome.turns, e);
+        }
+        let score_a = match outcome.winner {
+            Some(0) => 1f64,
+            Some(_) => 0f64,
+            None => 0.5,
+        };
+        scores[a][b] += score_a;
+        scores[b][a] += 1f64 - score_a;
+        played[a][b] += 1f64;
+        played[b][a] += 1f64;
+    }
+
+    let ratings = elo(&scores, &played);
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| ratings[b].partial_cmp(&ratings[a]).unwrap());
+    println!("{:>4}  {:>6}  {:>6}  {:>5}  bot", "rank", "elo", "score", "games");
+    for (rank, &i) in order.iter().enumerate() {
+        let total: f64 = played[i].iter().sum();
+        println!("{:>4}  {:>6.0}  {:>5.1}%  {:>5}  {}", rank + 1, ratings[i],
+                 100f64*scores[i].iter().sum::<f64>()/total, total, specs[i]);
+    }
+    println!();
+    println!("score of row against column:");
+    print!("{:>4}", "");
+    for rank in 0..n {
+        print!("  {:>6}", rank + 1);
+    }
+    println!();
+    for (rank, &i) in order.iter().enumerate() {
+        print!("{:>4}", rank + 1);
+        for &j in order.iter() {
+            if i == j {
+                print!("  {:>6}", "-");
+            } else {
+                print!("  {:>5.1}%", 100f64*scores[i][j]/played[i][j]);
+            }
+        }
+        println!();
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Scores and games of `n` bots where every pair played `games` games, `wins[i][j]` won by i.
+    fn table(wins: &[Vec<f64>], games: f64) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
+        let n = wins.len();
+        let played = (0..n).map(|i| (0..n).map(|j| if i == j {0f64} else {games}).collect()).collect();
+        (wins.to_vec(), played)
+    }
+
+    #[test]
+    fn dominant_bot_rates_highest() {
+        let (scores, played) = table(&[vec![0f64, 9f64, 10f64], vec![1f64, 0f64, 6f64], vec![0f64, 4f64, 0f64]], 10f64);
+        let ratings = elo(&scores, &played);
+        assert!((ratings[0] > ratings[1]) && (ratings[1] > ratings[2]), "{:?}", ratings);
+    }
+
+    #[test]
+    fn ratings_are_centered_on_the_base() {
+        let (scores, played) = table(&[vec![0f64, 9f64, 10f64], vec![1f64, 0f64, 6f64], vec![0f64, 4f64, 0f64]], 10f64);
+        let ratings = elo(&scores, &played);
+        let mean = ratings.iter().sum::<f64>()/(ratings.len() as f64);
+        assert!((mean - ELO_BASE).abs() < 1e-6);
+        // Even results leave everybody on the base.
+        let (scores, played) = table(&[vec![0f64, 5f64], vec![5f64, 0f64]], 10f64);
+        assert!(elo(&scores, &played).iter().all(|r| (r - ELO_BASE).abs() < 1e-6));
+    }
+
+    #[test]
+    fn rating_gap_only_depends_on_the_results() {
+        // Two bots: the strength ratio is the score ratio, the virtual draw included.
+        let gap = |wins: f64, games: f64| {
+            let (scores, played) = table(&[vec![0f64, wins], vec![games - wins, 0f64]], games);
+            let ratings = elo(&scores, &played);
+            ratings[0] - ratings[1]
+        };
+        let expected = 400f64*((7.5f64)/(3.5f64)).log10();
+        assert!((gap(7f64, 10f64) - expected).abs() < 1e-6);
+        assert!((gap(7f64, 10f64) + gap(3f64, 10f64)).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,138 @@
+extern crate caribbean;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::process;
+use std::sync::atomic::Ordering;
+
+use caribbean::arena;
+use caribbean::params::EvalParams;
+use caribbean::planner::PlannerKind;
+use caribbean::random::Random;
+use caribbean::strategy::StrategyKind;
+use caribbean::Game;
+
+const USAGE: &str = "usage: tuner [--generations N] [--candidates N] [--games N] [--seed N] [--threads N]
+             [--strategy heuristic|search] [--planner tree|beam|mcts] [--params FILE] [-o FILE]";
+
+// Weights left alone: the search depth decides the time used, the denominator only scales
+// the discount numerator.
+const FIXED: [&str; 2] = ["discount_den", "depth"];
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(2);
+}
+
+fn arg<T: std::str::FromStr>(value: Option<String>) -> T {
+    value.and_then(|s| s.parse().ok()).unwrap_or_else(|| fail(USAGE))
+}
+
+fn mutate(params: &EvalParams, random: &mut Random) -> EvalParams {
+    let mut candidate = params.clone();
+    for name in EvalParams::names().iter().filter(|name| !FIXED.contains(name)) {
+        // About one weight in three moves, by up to a fifth of its size.
+        if random.next_int(3) != 0 {
+            continue;
+        }
+        let value = candidate.field(name).unwrap();
+        let step = 1 + value.abs()/5;
+        *value = *value + random.next_int(2*step + 1) - step;
+    }
+    let den = candidate.discount_den;
+    let num = candidate.field("discount_num").unwrap();
+    *num = (*num).clamp(1, den);
+    candidate
+}
+
+// Plays `candidate` against `best` on `games` seeds, every seed from both sides, and returns
+// the candidate's score per game. Both bots are `base` with their own weights.
+fn evaluate(base: &Game, candidate: &EvalParams, best: &EvalParams, games: usize, first_seed: u64, threads: usize) -> Vec<f64> {
+    let bot = |params: &EvalParams| Game {params: params.clone(), ..base.clone()};
+    let (candidate, best) = (bot(candidate), bot(best));
+    arena::run_parallel(2*games, threads, |i| {
+        let seed = first_seed + (i/2) as u64;
+        let side = i%2;
+        let bots = if side == 0 {[candidate.clone(), best.clone()]} else {[best.clone(), candidate.clone()]};
+        match arena::play(seed, &bots).winner {
+            Some(winner) if winner == side => 1f64,
+            Some(_) => 0f64,
+            None => 0.5,
+        }
+    })
+}
+
+fn constants(params: &EvalParams) -> String {
+    let mut text = String::from("impl Default for EvalParams {\n    fn default() -> EvalParams {\n        EvalParams {\n");
+    for name in EvalParams::names().iter() {
+        text.push_str(&format!("            {}: {},\n", name, params.get(name).unwrap()));
+    }
+    text.push_str("        }\n    }\n}\n");
+    text
+}
+
+fn main() {
+    let mut generations = 10;
+    let mut candidates = 4;
+    let mut games = 20;
+    let mut seed = 1;
+    let mut threads = arena::default_threads();
+    let mut base = Game::default();
+    let mut best = EvalParams::default();
+    let mut output = None;
+    let mut args = env::args().skip(1);
+    while let Some(a) = args.next() {
+        match a.as_ref() {
+            "--generations" => generations = arg(args.next()),
+            "--candidates" => candidates = arg(args.next()),
+            "--games" => games = arg(args.next()),
+            "--seed" => seed = arg(args.next()),
+            "--threads" => threads = arg(args.next()),
+            "--strategy" => {
+                let name: String = arg(args.next());
+                base.strategy = StrategyKind::parse(&name).unwrap_or_else(|| fail(&format!("unknown strategy '{}'", name)));
+            },
+            "--planner" => {
+                let name: String = arg(args.next());
+                base.planner.kind = PlannerKind::parse(&name).unwrap_or_else(|| fail(&format!("unknown planner '{}'", name)));
+            },
+            "--params" => {
+                let path: String = arg(args.next());
+                let text = fs::read_to_string(&path).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+                best.parse(&text).unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+            },
+            "-o" => output = Some(arg::<String>(args.next())),
+            _ => fail(USAGE),
+        }
+    }
+    caribbean::LOG.store(false, Ordering::Relaxed);
+
+    // (1+λ) evolution: a candidate replaces the best set once it beats it with 95% confidence.
+    let mut random = Random::new(seed);
+    let mut first_seed = seed*1_000_000;
+    for generation in 0..generations {
+        let mut results = Vec::new();
+        for _ in 0..candidates {
+            let candidate = mutate(&best, &mut random);
+            let (rate, interval) = arena::win_rate(&evaluate(&base, &candidate, &best, games, first_seed, threads));
+            results.push((rate, interval, candidate));
+        }
+        first_seed = first_seed + games as u64;
+        results.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        let (rate, interval, candidate) = results.swap_remove(0);
+        let accepted = rate - interval > 0.5;
+        println!("generation {}: best candidate {:.3} +- {:.3} over {} games{}", generation, rate, interval,
+                 2*games, if accepted {", accepted"} else {""});
+        if accepted {
+            best = candidate;
+        }
+    }
+
+    let text = constants(&best);
+    print!("{}", text);
+    if let Some(path) = output {
+        File::create(&path).and_then(|mut file| file.write_all(text.as_bytes()))
+            .unwrap_or_else(|e| fail(&format!("{}: {}", path, e)));
+    }
+}
@@ -0,0 +1,223 @@
+use std::ops::{BitAnd, BitOr};
+use std::sync::OnceLock;
+
+use hex::{Cube, Point};
+
+pub const WIDTH: i32 = 23;
+pub const HEIGHT: i32 = 21;
+pub const TILES: usize = (WIDTH*HEIGHT) as usize;
+// Neighbour table entry for a step that leaves the map.
+pub const OFF_BOARD: usize = TILES;
+const WORDS: usize = TILES.div_ceil(64);
+
+// Lookup tables over the fixed map, built on first use by `Board::get`.
+pub struct Board {
+    neighbours: Vec<[usize; 6]>,
+    distances: Vec<u8>,
+}
+
+static BOARD: OnceLock<Board> = OnceLock::new();
+
+impl Board {
+    pub fn get() -> &'static Board {
+        BOARD.get_or_init(Board::build)
+    }
+
+    fn build() -> Board {
+        let mut neighbours = vec![[OFF_BOARD; 6]; TILES];
+        let mut distances = vec![0u8; TILES*TILES];
+        for i in 0..TILES {
+            let cube = Cube::from(Board::point(i));
+            for (rotation, neighbour) in neighbours[i].iter_mut().enumerate() {
+                let next = Point::from(cube.neighbour(rotation as i32));
+                if next.is_inside() {
+                    *neighbour = Board::index(&next);
+                }
+            }
+            for j in 0..TILES {
+                distances[i*TILES + j] = cube.distance(&Cube::from(Board::point(j))) as u8;
+            }
+        }
+        Board {
+            neighbours: neighbours,
+            distances: distances,
+        }
+    }
+
+    // Flat index of a tile, rows first. The point has to be inside the map.
+    pub fn index(point: &Point) -> usize {
+        (point.y*WIDTH + point.x) as usize
+    }
+
+    pub fn point(index: usize) -> Point {
+        Point::new(index as i32 % WIDTH, index as i32 / WIDTH)
+    }
+
+    pub fn neighbour(&self, index: usize, rotation: i32) -> usize {
+        self.neighbours[index][rotation as usize]
+    }
+
+    pub fn distance(&self, a: usize, b: usize) -> i32 {
+        self.distances[a*TILES + b] as i32
+    }
+}
+
+// One bit per tile.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub struct Bitboard {
+    words: [u64; WORDS],
+}
+
+impl Bitboard {
+    pub fn insert(&mut self, point: &Point) {
+        if point.is_inside() {
+            let i = Board::index(point);
+            self.words[i/64] |= 1 << (i%64);
+        }
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        if !point.is_inside() {
+            return false;
+        }
+        let i = Board::index(point);
+        self.words[i/64] & (1 << (i%64)) != 0
+    }
+
+    pub fn clear(&mut self) {
+        self.words = [0; WORDS];
+    }
+
+    // The three tiles of a ship centered on `point`, parts off the map left out.
+    pub fn hull(point: &Point, rotation: i32) -> Bitboard {
+        let mut hull = Bitboard::default();
+        hull.insert(&point.neighbour((rotation + 3)%6));
+        hull.insert(point);
+        hull.insert(&point.neighbour(rotation));
+        hull
+    }
+
+    pub fn count(&self) -> i32 {
+        self.words.iter().map(|word| word.count_ones() as i32).sum()
+    }
+
+    fn zip_with<F: Fn(u64, u64) -> u64>(self, other: Bitboard, f: F) -> Bitboard {
+        let mut result = self;
+        for (word, other) in result.words.iter_mut().zip(other.words.iter()) {
+            *word = f(*word, *other);
+        }
+        result
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, other: Bitboard) -> Bitboard {
+        self.zip_with(other, |a, b| a | b)
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, other: Bitboard) -> Bitboard {
+        self.zip_with(other, |a, b| a & b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_and_point_round_trip() {
+        for i in 0..TILES {
+            assert_eq!(Board::index(&Board::point(i)), i);
+        }
+        assert_eq!(Board::point(TILES - 1), Point::new(WIDTH - 1, HEIGHT - 1));
+    }
+
+    #[test]
+    fn neighbours_match_cube_steps() {
+        let board = Board::get();
+        for i in 0..TILES {
+            let cube = Cube::from(Board::point(i));
+            for rotation in 0..6 {
+                let next = Point::from(cube.neighbour(rotation));
+                let expected = if next.is_inside() {Board::index(&next)} else {OFF_BOARD};
+                assert_eq!(board.neighbour(i, rotation), expected, "{:?} {}", Board::point(i), rotation);
+            }
+        }
+        // Corners and edges step off the map.
+        assert_eq!(board.neighbour(Board::index(&Point::new(0, 0)), 3), OFF_BOARD);
+        assert_eq!(board.neighbour(Board::index(&Point::new(0, 0)), 1), OFF_BOARD);
+        assert_eq!(board.neighbour(Board::index(&Point::new(WIDTH - 1, 10)), 0), OFF_BOARD);
+        assert_eq!(board.neighbour(Board::index(&Point::new(5, HEIGHT - 1)), 4), OFF_BOARD);
+    }
+
+    #[test]
+    fn distances_match_cube_distances() {
+        let board = Board::get();
+        for i in 0..TILES {
+            let cube = Cube::from(Board::point(i));
+            for j in 0..TILES {
+                assert_eq!(board.distance(i, j), cube.distance(&Cube::from(Board::point(j))));
+            }
+        }
+        assert_eq!(board.distance(Board::index(&Point::new(0, 0)), Board::index(&Point::new(WIDTH - 1, HEIGHT - 1))), 32);
+    }
+
+    #[test]
+    fn bitboard_holds_tiles_inside_the_map() {
+        let mut board = Bitboard::default();
+        let points = [Point::new(0, 0), Point::new(22, 20), Point::new(7, 3), Point::new(7, 3), Point::new(-1, 4), Point::new(23, 0)];
+        for point in points.iter() {
+            board.insert(point);
+        }
+        assert_eq!(board.count(), 3);
+        assert!(board.contains(&Point::new(0, 0)) && board.contains(&Point::new(22, 20)) && board.contains(&Point::new(7, 3)));
+        assert!(!board.contains(&Point::new(3, 7)));
+        assert!(!board.contains(&Point::new(-1, 4)));
+        board.clear();
+        assert!(board == Bitboard::default());
+    }
+
+    #[test]
+    fn hull_covers_stern_center_and_bow() {
+        let hull = Bitboard::hull(&Point::new(10, 10), 1);
+        assert_eq!(hull.count(), 3);
+        for point in [Point::new(10, 10), Point::new(10, 10).neighbour(1), Point::new(10, 10).neighbour(4)].iter() {
+            assert!(hull.contains(point));
+        }
+        // A bow hanging off the map is left out.
+        assert_eq!(Bitboard::hull(&Point::new(22, 10), 0).count(), 2);
+    }
+
+    #[test]
+    fn bitboards_combine_by_tile() {
+        let (mut a, mut b) = (Bitboard::default(), Bitboard::default());
+        a.insert(&Point::new(1, 1));
+        a.insert(&Point::new(20, 19));
+        b.insert(&Point::new(20, 19));
+        b.insert(&Point::new(5, 12));
+        let union = a | b;
+        assert_eq!(union.count(), 3);
+        assert!(union.contains(&Point::new(1, 1)) && union.contains(&Point::new(5, 12)));
+        let common = a & b;
+        assert_eq!(common.count(), 1);
+        assert!(common.contains(&Point::new(20, 19)));
+    }
+
+    #[test]
+    fn points_step_through_the_table_and_off_the_map() {
+        for i in 0..TILES {
+            let point = Board::point(i);
+            for rotation in 0..6 {
+                assert_eq!(point.neighbour(rotation), Point::from(Cube::from(point).neighbour(rotation)));
+            }
+        }
+        assert_eq!(Point::new(0, 0).neighbour(3), Point::new(-1, 0));
+        assert_eq!(Point::new(-1, 0).neighbour(0), Point::new(0, 0));
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use board::Board;
+use game::Game;
+use routing::{self, UNREACHABLE};
+
+// Extra distance an enemy counts for each of our ships already shooting at it, so the ships
+// spread their fire unless one enemy is much closer.
+const SHARED_TARGET_PENALTY: i32 = 5;
+
+// Barrel and enemy picked for each of our ships on this turn.
+#[derive(Default, Clone)]
+pub struct Assignment {
+    pub barrels: BTreeMap<i32, i32>,
+    pub targets: BTreeMap<i32, i32>,
+}
+
+impl Game {
+    // Greedy auction: the free (ship, barrel) pair with the most rum per turn of travel goes
+    // first, so two ships never chase the same barrel. Barrels an enemy reaches sooner are left
+    // alone, and ships left without a barrel head for their waypoint.
+    pub fn assign(&self) -> Assignment {
+        let mut assignment = Assignment::default();
+        let ships: Vec<_> = self.my_ships_ids.iter()
+            .map(|id| &self.my_ships[id])
+            .filter(|ship| ship.is_alive(self.current_tick))
+            .collect();
+        let enemy_arrivals: Vec<Vec<i32>> = self.enemy_ships.values()
+            .filter(|enemy_ship| enemy_ship.is_alive(self.current_tick))
+            .map(|enemy_ship| routing::arrival_turns(enemy_ship, &self.mine_field))
+            .collect();
+        let mut pairs = Vec::new();
+        for ship in ships.iter() {
+            let arrival = routing::arrival_turns(ship, &self.mine_field);
+            for barrel in self.barrels.values() {
+                if !barrel.is_alive(self.current_tick) {
+                    continue;
+                }
+                let tile = Board::index(&barrel.point);
+                let turns = match arrival[tile] {
+                    UNREACHABLE => ship.point.distance(&barrel.point),
+                    turns => turns,
+                };
+                if enemy_arrivals.iter().any(|enemy_arrival| enemy_arrival[tile] < turns) {
+                    continue;
+                }
+                pairs.push((routing::barrel_value(ship, barrel, turns), ship.entity_id, barrel.entity_id));
+            }
+        }
+        pairs.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap().then((a.1, a.2).cmp(&(b.1, b.2))));
+        for (_, ship_id, barrel_id) in pairs {
+            if assignment.barrels.contains_key(&ship_id) || assignment.barrels.values().any(|&b| b == barrel_id) {
+                continue;
+            }
+            assignment.barrels.insert(ship_id, barrel_id);
+        }
+        let mut shooters: BTreeMap<i32, i32> = BTreeMap::new();
+        for ship in ships.iter() {
+            let bow = ship.point.neighbour(ship.rotation);
+            let target = self.enemy_ships.values()
+                .filter(|enemy_ship| enemy_ship.is_alive(self.current_tick))
+                .min_by_key(|enemy_ship| {
+                    bow.distance(&enemy_ship.point) + SHARED_TARGET_PENALTY*shooters.get(&enemy_ship.entity_id).cloned().unwrap_or(0)
+                })
+                .map(|enemy_ship| enemy_ship.entity_id);
+            if let Some(target) = target {
+                *shooters.entry(target).or_insert(0) += 1;
+                assignment.targets.insert(ship.entity_id, target);
+            }
+        }
+        assignment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entities::{Barrel, Ship};
+    use referee::MAX_SHIP_RUM;
+    use super::*;
+
+    fn game(mine: &[Ship], enemy: &[Ship], barrels: &[Barrel]) -> Game {
+        let mut game = Game::default();
+        for ship in mine.iter() {
+            game.my_ships_ids.push(ship.entity_id);
+            game.my_ships.insert(ship.entity_id, ship.clone());
+        }
+        for ship in enemy.iter() {
+            game.enemy_ships.insert(ship.entity_id, ship.clone());
+        }
+        for barrel in barrels.iter() {
+            game.barrels.insert(barrel.entity_id, barrel.clone());
+        }
+        game
+    }
+
+    fn ship(entity_id: i32, x: i32, y: i32, rotation: i32) -> Ship {
+        Ship::new(entity_id, x, y, rotation, 1, 50)
+    }
+
+    #[test]
+    fn one_barrel_goes_to_one_ship() {
+        let game = game(&[ship(0, 5, 10, 0), ship(2, 16, 10, 3)], &[], &[Barrel::new(4, 9, 10, 15)]);
+        let assignment = game.assign();
+        assert_eq!(assignment.barrels.len(), 1);
+        assert_eq!(assignment.barrels.get(&0), Some(&4));
+    }
+
+    #[test]
+    fn barrel_the_enemy_reaches_first_is_left_alone() {
+        let mine = [ship(0, 3, 10, 0)];
+        let enemy = [ship(1, 12, 10, 3)];
+        let barrels = [Barrel::new(4, 10, 10, 15), Barrel::new(5, 3, 16, 10)];
+        let alone = game(&mine, &[], &barrels[..1]);
+        assert_eq!(alone.assign().barrels.get(&0), Some(&4));
+        let raced = game(&mine, &enemy, &barrels[..1]);
+        assert!(raced.assign().barrels.is_empty());
+        let other = game(&mine, &enemy, &barrels);
+        assert_eq!(other.assign().barrels.get(&0), Some(&5));
+    }
+
+    #[test]
+    fn second_shooter_picks_another_enemy_when_close_enough() {
+        let (first, second) = (ship(0, 5, 5, 0), ship(2, 5, 7, 0));
+        let near = Ship::new(1, 10, 6, 0, 0, MAX_SHIP_RUM);
+        let bow = second.point.neighbour(0);
+        for &(x, spread) in [(12, true), (20, false)].iter() {
+            let far = Ship::new(3, x, 6, 0, 0, MAX_SHIP_RUM);
+            assert_eq!(bow.distance(&far.point) - bow.distance(&near.point) < SHARED_TARGET_PENALTY, spread);
+            let assignment = game(&[first.clone(), second.clone()], &[near.clone(), far], &[]).assign();
+            assert_eq!(assignment.targets.get(&0), Some(&1));
+            assert_eq!(assignment.targets.get(&2), Some(if spread {&3} else {&1}));
+        }
+    }
+}
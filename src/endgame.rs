@@ -0,0 +1,221 @@
+use std::cmp;
+
+use action::Action;
+use entities::Ship;
+use game::Game;
+use hex::Point;
+use referee::{self, FIRE_DISTANCE_MAX, HIGH_DAMAGE, LOW_DAMAGE, MINE_DAMAGE};
+use targeting::MOVES;
+
+// Turns searched exhaustively, the rest of the game is played out greedily.
+const ENDGAME_DEPTH: i32 = 4;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Stance {
+    // Ahead on rum: stay out of range and dodge everything.
+    Evade,
+    // Behind: close in on the richest enemy and shoot.
+    Attack,
+}
+
+// Rum a side ends the game with when nobody heals any more, then how long its last ship lasts,
+// which breaks the tie when both sides would sink.
+fn forecast<'a, I: Iterator<Item = &'a Ship>>(ships: I, turns_left: i32) -> (i32, i32) {
+    ships.fold((0, 0), |(total, longest), ship| (total + cmp::max(0, ship.rum - turns_left), cmp::max(longest, ship.rum)))
+}
+
+impl Game {
+    // Once the barrels are gone the rum only goes down, so whoever has more of it when the
+    // turns run out wins. When we only win by outlasting the enemy ships the usual patrol,
+    // which keeps moving, does better than either stance.
+    pub fn endgame(&self) -> Option<Stance> {
+        if self.barrels.values().any(|barrel| barrel.is_alive(self.current_tick)) {
+            return None;
+        }
+        let turns_left = self.turns_left();
+        let mine = forecast(self.my_ships.values().filter(|ship| ship.is_alive(self.current_tick)), turns_left);
+        let enemy = forecast(self.enemy_ships.values().filter(|ship| ship.is_alive(self.current_tick)), turns_left);
+        if mine.0 > enemy.0 {
+            Some(Stance::Evade)
+        } else if mine < enemy {
+            Some(Stance::Attack)
+        } else {
+            None
+        }
+    }
+
+    // Searches every line of moves over the next turns, then follows the best one with a greedy
+    // rollout until the game ends, so the whole remaining horizon is planned for.
+    pub fn endgame_move(&self, ship: &Ship, stance: Stance) -> Action {
+        let horizon = cmp::max(1, self.turns_left());
+        let depth = cmp::min(ENDGAME_DEPTH, horizon);
+        let enemies: Vec<&Ship> = self.enemy_ships.values().filter(|enemy_ship| enemy_ship.is_alive(self.current_tick)).collect();
+        // Where every enemy is after each turn, assuming it keeps going straight.
+        let tracks: Vec<Vec<Point>> = enemies.iter().map(|enemy_ship| {
+            let mut state = (enemy_ship.point, enemy_ship.rotation, enemy_ship.speed);
+            (0..(horizon + 1)).map(|_| {
+                let point = state.0;
+                state = referee::move_alone(state.0, state.1, state.2, Action::WAIT);
+                point
+            }).collect()
+        }).collect();
+        let richest = (0..enemies.len()).max_by_key(|&i| (enemies[i].rum, -enemies[i].entity_id));
+        let target = richest.map(|i| tracks[i].as_slice());
+        let mut best = (i32::MIN, Action::WAIT);
+        for &action in MOVES.iter() {
+            let (value, leaf) = self.endgame_value((ship.point, ship.rotation, ship.speed), action, 1, depth, stance, &tracks, target);
+            let value = value + self.endgame_rollout(leaf, depth, horizon, stance, &tracks, target);
+            if value > best.0 {
+                best = (value, action);
+            }
+        }
+        best.1
+    }
+
+    // Damage taken on `turn` from known cannonballs and mines, plus the distance score of the
+    // stance.
+    fn endgame_score(&self, state: (Point, i32, i32), turn: i32, stance: Stance, tracks: &[Vec<Point>], target: Option<&[Point]>) -> i32 {
+        let (point, rotation, speed) = state;
+        let hull = [point.neighbour(rotation), point.neighbour((rotation + 3)%6)];
+        let mut value = 0;
+        if speed == 0 {
+            value = value - 1;
+        }
+        if let Some(impacts) = self.impact_field.get((turn - 1) as usize) {
+            if impacts.contains(&point) {
+                value = value - HIGH_DAMAGE;
+            }
+            value = value - LOW_DAMAGE*(hull.iter().filter(|tile| impacts.contains(tile)).count() as i32);
+        }
+        if self.mine_field.contains(&point) || hull.iter().any(|tile| self.mine_field.contains(tile)) {
+            value = value - MINE_DAMAGE;
+        }
+        match stance {
+            Stance::Evade => {
+                let closest = tracks.iter().map(|track| track[turn as usize].distance(&point)).min().unwrap_or(FIRE_DISTANCE_MAX);
+                value = value + cmp::min(closest, FIRE_DISTANCE_MAX + 1);
+            },
+            Stance::Attack => {
+                if let Some(track) = target {
+                    // Close enough for a short flight, not so close as to get rammed.
+                    let d = track[turn as usize].distance(&point);
+                    value = value - (d - 3).abs();
+                }
+            },
+        }
+        value
+    }
+
+    // Score of the best line of moves starting with `action` on `turn`, down to `depth`, and
+    // where that line leaves the ship.
+    fn endgame_value(&self, state: (Point, i32, i32), action: Action, turn: i32, depth: i32,
+                     stance: Stance, tracks: &[Vec<Point>], target: Option<&[Point]>) -> (i32, (Point, i32, i32)) {
+        let state = referee::move_alone(state.0, state.1, state.2, action);
+        let value = self.endgame_score(state, turn, stance, tracks, target);
+        if turn >= depth {
+            return (value, state);
+        }
+        let (next, leaf) = MOVES.iter()
+            .map(|&next| self.endgame_value(state, next, turn + 1, depth, stance, tracks, target))
+            .max_by_key(|&(next, _)| next)
+            .unwrap();
+        (value + next, leaf)
+    }
+
+    // Turns `from + 1` to `to` played by taking the move that scores best on each turn alone.
+    fn endgame_rollout(&self, state: (Point, i32, i32), from: i32, to: i32,
+                       stance: Stance, tracks: &[Vec<Point>], target: Option<&[Point]>) -> i32 {
+        let mut state = state;
+        let mut value = 0;
+        for turn in (from + 1)..(to + 1) {
+            let (score, next) = MOVES.iter()
+                .map(|&action| {
+                    let next = referee::move_alone(state.0, state.1, state.2, action);
+                    (self.endgame_score(next, turn, stance, tracks, target), next)
+                })
+                .max_by_key(|&(score, _)| score)
+                .unwrap();
+            value = value + score;
+            state = next;
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entities::Barrel;
+    use referee::MAX_TURNS;
+    use super::*;
+
+    // Our ship and an enemy ship, `turns_left` before the end and no barrel left.
+    fn game(mine: Ship, enemy: Ship, turns_left: i32) -> Game {
+        let mut game = Game {current_tick: MAX_TURNS - turns_left, ..Game::default()};
+        for mut ship in [mine, enemy].iter().cloned() {
+            ship.tick_accessed = game.current_tick;
+            if ship.entity_id%2 == 0 {
+                game.my_ships_ids.push(ship.entity_id);
+                game.my_ships.insert(ship.entity_id, ship);
+            } else {
+                game.enemy_ships.insert(ship.entity_id, ship);
+            }
+        }
+        game
+    }
+
+    // Distances to the enemy while our ship follows `stance` for `turns` turns, the enemy
+    // staying where it is.
+    fn follow(mut game: Game, stance: Stance, turns: i32) -> Vec<i32> {
+        let mut distances = Vec::new();
+        for _ in 0..turns {
+            let ship = game.my_ships[&0].clone();
+            let action = game.endgame_move(&ship, stance);
+            let (point, rotation, speed) = referee::move_alone(ship.point, ship.rotation, ship.speed, action);
+            game.current_tick += 1;
+            let tick = game.current_tick;
+            game.my_ships.get_mut(&0).unwrap().update(tick, point.x, point.y, rotation, speed, ship.rum - 1);
+            game.enemy_ships.get_mut(&1).unwrap().tick_accessed = tick;
+            distances.push(point.distance(&game.enemy_ships[&1].point));
+        }
+        distances
+    }
+
+    #[test]
+    fn stance_follows_the_rum_forecast() {
+        let ahead = game(Ship::new(0, 5, 5, 0, 1, 80), Ship::new(1, 15, 15, 3, 1, 60), 20);
+        assert_eq!(ahead.endgame(), Some(Stance::Evade));
+        let behind = game(Ship::new(0, 5, 5, 0, 1, 60), Ship::new(1, 15, 15, 3, 1, 80), 20);
+        assert_eq!(behind.endgame(), Some(Stance::Attack));
+        let mut barrels_left = ahead.clone();
+        barrels_left.barrels.insert(2, Barrel::new(2, 10, 10, 10));
+        barrels_left.barrels.get_mut(&2).unwrap().keep_alive(barrels_left.current_tick);
+        assert_eq!(barrels_left.endgame(), None);
+        // Both sides sink before the end and ours lasts longer: only patrolling keeps it so.
+        let outlasting = game(Ship::new(0, 5, 5, 0, 1, 15), Ship::new(1, 15, 15, 3, 1, 10), 20);
+        assert_eq!(outlasting.endgame(), None);
+    }
+
+    #[test]
+    fn evading_ship_keeps_out_of_range() {
+        let game = game(Ship::new(0, 8, 10, 0, 1, 80), Ship::new(1, 11, 10, 3, 0, 60), 30);
+        let distances = follow(game, Stance::Evade, 12);
+        assert!(distances[11] > FIRE_DISTANCE_MAX, "{:?}", distances);
+    }
+
+    #[test]
+    fn attacking_ship_closes_in() {
+        let game = game(Ship::new(0, 3, 3, 0, 1, 80), Ship::new(1, 15, 15, 3, 0, 60), 30);
+        let distances = follow(game, Stance::Attack, 15);
+        // Then it hovers at a short shot's distance.
+        assert!(distances[8..].iter().all(|&d| (2..=5).contains(&d)), "{:?}", distances);
+    }
+
+    #[test]
+    fn rollout_plays_every_remaining_turn() {
+        let game = game(Ship::new(0, 3, 3, 0, 1, 80), Ship::new(1, 15, 15, 3, 0, 60), 30);
+        let state = (Point::new(5, 10), 0, 1);
+        assert_eq!(game.endgame_rollout(state, 4, 4, Stance::Evade, &[], None), 0);
+        // Open water and no enemy: every turn scores the full range for a moving ship.
+        assert_eq!(game.endgame_rollout(state, 4, 9, Stance::Evade, &[], None), 5*FIRE_DISTANCE_MAX);
+    }
+}
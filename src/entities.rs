@@ -0,0 +1,147 @@
+use hex::Point;
+
+#[derive(Clone)]
+pub struct Ship {
+    pub entity_id: i32,
+    pub point: Point,
+    pub rotation: i32,
+    pub speed: i32,
+    pub rum: i32,
+    pub tick_accessed: i32,
+    pub cd: i32,
+    pub mine_cd: i32,
+    pub wp_ind: usize,
+}
+
+#[derive(Clone)]
+pub struct Barrel {
+    pub entity_id: i32,
+    pub point: Point,
+    pub quantity: i32,
+    pub tick_accessed: i32,
+}
+
+#[derive(Clone)]
+pub struct Mine {
+    pub entity_id: i32,
+    pub point: Point,
+    pub tick_accessed: i32,
+    pub under_fire: bool,
+}
+
+#[derive(Clone)]
+pub struct Cannoball {
+    pub entity_id: i32,
+    pub owner_id: i32,
+    pub impact_time: i32,
+    pub target: Point,
+    pub tick_accessed: i32,
+}
+
+impl Ship {
+    pub fn new(entity_id: i32, x: i32, y: i32,
+           rotation: i32, speed: i32, rum: i32) -> Ship {
+        Ship {
+            entity_id: entity_id,
+            point: Point::new(x, y),
+            rotation: rotation,
+            speed: speed,
+            rum: rum,
+            tick_accessed: 0,
+            cd: 0,
+            mine_cd: 0,
+            wp_ind: (entity_id as usize)%4,
+        }
+    }
+
+    pub fn update(&mut self, current_tick: i32, x: i32, y: i32,
+           rotation: i32, speed: i32, rum: i32) {
+        self.tick_accessed = current_tick;
+        self.point.x = x;
+        self.point.y = y;
+        self.rotation = rotation;
+        self.speed = speed;
+        self.rum = rum;
+        if self.cd > 0 {
+            self.cd = self.cd - 1;
+        }
+        if self.mine_cd > 0 {
+            self.mine_cd = self.mine_cd - 1;
+        }
+    }
+
+    pub fn set_cd(&mut self, cd: i32) {
+        self.cd = cd
+    }
+
+    pub fn set_mine_cd(&mut self, mine_cd: i32) {
+        self.mine_cd = mine_cd
+    }
+
+    pub fn is_alive(&self, current_tick: i32) -> bool {
+        current_tick == self.tick_accessed
+    }
+}
+
+impl Barrel {
+    pub fn new(entity_id: i32, x: i32, y: i32, quantity: i32) -> Barrel {
+        Barrel {
+            entity_id: entity_id,
+            point: Point::new(x, y),
+            quantity: quantity,
+            tick_accessed: 0,
+        }
+    }
+
+    pub fn keep_alive(&mut self, current_tick: i32) {
+        self.tick_accessed = current_tick
+    }
+
+    pub fn is_alive(&self, current_tick: i32) -> bool {
+        current_tick == self.tick_accessed
+    }
+}
+
+impl Mine {
+    pub fn new(entity_id: i32, x: i32, y: i32) -> Mine {
+        Mine {
+            entity_id: entity_id,
+            point: Point::new(x, y),
+            tick_accessed: 0,
+            under_fire: false,
+        }
+    }
+
+    pub fn keep_alive(&mut self, current_tick: i32) {
+        self.tick_accessed = current_tick
+    }
+
+    pub fn is_alive(&self, current_tick: i32) -> bool {
+        current_tick == self.tick_accessed
+    }
+
+    pub fn set_under_fire(&mut self) {
+        self.under_fire = true;
+    }
+}
+
+impl Cannoball {
+    pub fn new(entity_id: i32, owner_id: i32, impact_time: i32, x: i32, y: i32) -> Cannoball {
+        Cannoball {           
+            entity_id: entity_id,
+            owner_id: owner_id,
+            impact_time: impact_time,
+            target: Point::new(x, y),
+            tick_accessed: 0,
+        }
+    }
+
+    pub fn keep_alive(&mut self, current_tick: i32) {
+        self.tick_accessed = current_tick;
+        self.impact_time = self.impact_time - 1;
+    }
+
+    pub fn is_alive(&self, current_tick: i32) -> bool {
+        current_tick == self.tick_accessed
+    }
+}
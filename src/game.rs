@@ -0,0 +1,372 @@
+use std::io::BufRead;
+use std::vec::Vec;
+use std::collections::BTreeMap;
+use std::f64;
+use std::cmp;
+
+use action::Action;
+use board::Bitboard;
+use entities::{Barrel, Cannoball, Mine, Ship};
+use hex::Point;
+use opponent::{self, OpponentModel};
+use params::EvalParams;
+use planner::Planner;
+use protocol::{self, EntityRecord, ParseError, TurnInput};
+use strategy::StrategyKind;
+use timer::Timer;
+use tracker::Tracker;
+use referee::{self, Order, COOLDOWN_CANNON, COOLDOWN_MINE};
+
+// Cannonballs land at most 4 turns after being fired.
+pub const IMPACT_TURNS: usize = 5;
+
+#[derive(Default, Clone)]
+pub struct Game {
+    pub my_ships: BTreeMap<i32, Ship>,
+    pub my_ships_ids: Vec<i32>,
+    pub enemy_ships: BTreeMap<i32, Ship>,
+    pub barrels: BTreeMap<i32, Barrel>,
+    pub mines: BTreeMap<i32, Mine>,
+    pub cannonballs: BTreeMap<i32, Cannoball>,
+    pub current_tick: i32,
+    // Our ships still afloat, as given by the referee.
+    pub ship_count: i32,
+    // Tiles hit by a cannonball, indexed by the cannonball's `impact_time`.
+    pub impact_field: [Bitboard; IMPACT_TURNS],
+    pub barrels_field: Bitboard,
+    pub mine_field: Bitboard,
+    // Hulls of all the alive ships.
+    pub hull_field: Bitboard,
+    pub lines_read: usize,
+    pub planner: Planner,
+    pub strategy: StrategyKind,
+    pub params: EvalParams,
+    pub timer: Timer,
+    pub opponent: OpponentModel,
+    pub tracker: Tracker,
+}
+
+impl Game {
+    pub fn check_position(&self, point: &Point, rotation: i32, _speed: i32, depth: i32) -> i32 {
+        let hull = Bitboard::hull(point, rotation);
+        let params = &self.params;
+        let mut value = params.barrel_tile*(hull & self.barrels_field).count() + params.mine_tile*(hull & self.mine_field).count();
+        if let Some(impacts) = self.impact_field.get((depth - 1) as usize) {
+            value = value + params.impact_end*(hull & *impacts).count();
+            if impacts.contains(point) {
+                value = value + params.impact_center - params.impact_end;
+            }
+        }
+        value
+    }
+
+    // Orders of the other alive ships for every turn of the movement search: our ships follow
+    // the order they already took this turn (`committed`) and then WAIT, the enemies keep their
+    // likely speed. Past the first turn they only run into each other.
+    pub fn traffic(&self, ship_id: i32, committed: &BTreeMap<i32, Action>) -> Vec<Vec<Order>> {
+        let tick = self.current_tick;
+        let mut orders: Vec<Order> = self.my_ships.values()
+            .filter(|ship| ship.is_alive(tick) && (ship.entity_id != ship_id))
+            .map(|ship| (ship.point, ship.rotation, ship.speed, committed.get(&ship.entity_id).cloned().unwrap_or(Action::WAIT)))
+            .chain(self.enemy_ships.values()
+                   .filter(|ship| ship.is_alive(tick))
+                   .map(|ship| (ship.point, ship.rotation, self.opponent.likely_speed(ship), Action::WAIT)))
+            .collect();
+        let mut turns = Vec::new();
+        for _ in 0..cmp::max(1, self.params.depth) {
+            let moved = referee::move_fleet(&orders);
+            turns.push(orders);
+            orders = moved.into_iter().map(|(point, rotation, speed, _)| (point, rotation, speed, Action::WAIT)).collect();
+        }
+        turns
+    }
+
+    // Our ship's `action` played among the `orders` of the other ships with the referee's rules:
+    // where it ends up and whether it ran into a ship, in which case it stays put at speed 0.
+    pub fn check_collision(point: &Point, rotation: i32, speed: i32, action: Action, orders: &[Order]) -> (Point, i32, i32, bool) {
+        let mut fleet = orders.to_vec();
+        fleet.push((*point, rotation, speed, action));
+        referee::move_fleet(&fleet).pop().unwrap()
+    }
+
+    pub fn move_recur(&self, dest: &Point, point: &Point, rotation: i32, speed: i32, action: Action, depth: i32, traffic: &[Vec<Order>]) -> (i32, bool) {
+        let d = point.distance(dest);
+        let angle = point.angle(dest);
+        let angle_straight = f64::min((rotation as f64 - angle).abs(), 6f64 - (rotation as f64  - angle).abs());
+        let (t_point, new_rotation, speed, collision) =
+            Game::check_collision(point, rotation, speed, action, &traffic[(depth - 1) as usize]);
+        let mut value = self.check_position(&t_point, rotation, speed, depth);
+        if collision {
+            return (value, true);
+        }
+        let rotation = new_rotation;
+        if (action == Action::PORT) || (action == Action::STARBOARD) {
+            value = value + self.check_position(&t_point, rotation, speed, depth);
+        }
+        let d_new = t_point.distance(dest);
+        let angle_new = t_point.angle(dest);
+        let angle_straighte_new = f64::min((rotation as f64 - angle_new).abs(), 6f64 - (rotation as f64  - angle_new).abs());
+        let params = &self.params;
+        if d_new < d {
+            value = value + params.closer;
+        }
+        if (t_point.x <= 1) && ((rotation == 3) || (rotation == 2) || (rotation == 4)) {
+           value = value + params.edge;
+        }
+        
+        if (t_point.y <= 1) && ((rotation == 1) || (rotation == 2)) {
+           value = value + params.edge;
+        }
+        
+        if (t_point.x >= 21) && ((rotation == 0) || (rotation == 1) || (rotation == 5)) {
+            value = value + params.edge;
+        }
+        if (t_point.y >= 19 ) && ((rotation == 4) || (rotation == 5)) {
+            value = value + params.edge;
+        }
+        if angle_straighte_new < angle_straight {
+            value = value + params.straighter;
+        }
+        if speed == 0 {
+            value = value + params.stopped;
+        }
+        if depth < params.depth {
+            let discount = |t_val: i32| params.discount_num*t_val/params.discount_den;
+            let (t_val, _) = self.move_recur(dest, &t_point, rotation, speed, Action::WAIT, depth+1, traffic);
+            let mut m_val = discount(t_val);
+            let (t_val, _) = self.move_recur(dest, &t_point, rotation, speed, Action::PORT, depth+1, traffic);
+            m_val = cmp::max(m_val, discount(t_val));
+            let (t_val, _) = self.move_recur(dest, &t_point, rotation, speed, Action::STARBOARD, depth+1, traffic);
+            m_val = cmp::max(m_val, discount(t_val));
+            let (t_val, _) = self.move_recur(dest, &t_point, rotation, speed, Action::FASTER, depth+1, traffic);
+            m_val = cmp::max(m_val, discount(t_val));
+            let (t_val, _) = self.move_recur(dest, &t_point, rotation, speed, Action::SLOWER, depth+1, traffic);
+            m_val = cmp::max(m_val, discount(t_val));
+            value = value + m_val;
+        }
+        (value, false)
+    }
+
+    pub fn move_to(&self, dest: &Point, point: &Point, rotation: i32, speed: i32, ship_id: i32,
+                   committed: &BTreeMap<i32, Action>) -> Action {
+        let traffic = self.traffic(ship_id, committed);
+        let (mut value, _) = self.move_recur(dest, point, rotation, speed, Action::WAIT, 1, &traffic);
+        if speed > 0 {
+            value = value + self.params.moving;
+        }
+        let mut result = Action::WAIT;
+        let (t_val1, t_coll) = self.move_recur(dest, point, rotation, speed, Action::PORT, 1, &traffic);
+        if (t_val1 >= value) && (!t_coll) {
+            value = t_val1;
+            result = Action::PORT; 
+        }
+        let (t_val2, t_coll) = self.move_recur(dest, point, rotation, speed, Action::STARBOARD, 1, &traffic);
+        if (t_val2 >= value) && (!t_coll) {
+            value = t_val2;
+            result = Action::STARBOARD; 
+        }
+        let (t_val3, t_coll) = self.move_recur(dest, point, rotation, speed, Action::FASTER, 1, &traffic);
+        if (t_val3 >= value) && (speed < 2) && (!t_coll) {
+            value = t_val3;
+            result = Action::FASTER; 
+        }
+        let (t_val4, t_coll) = self.move_recur(dest, point, rotation, speed, Action::SLOWER, 1, &traffic);
+        if (t_val4 >= value) && (speed > 0) && (!t_coll) {
+            value = t_val4;
+            result = Action::SLOWER; 
+        }
+        print_err!("{} {} {} {} {}", value, t_val1, t_val2, t_val3, t_val4);
+        result
+    }
+
+    pub fn init<R: BufRead>(&mut self, input: &mut R) -> Result<Vec<Action>, ParseError> {
+        self.current_tick = 0;
+        let turn = protocol::read_turn(input, &mut self.lines_read)?;
+        self.load_turn(turn);
+        self.calc_under_fire();
+        Ok(self.do_next_turn())
+    }
+
+    fn load_turn(&mut self, turn: TurnInput) {
+        self.timer.start_at(turn.received, Timer::turn_budget(self.current_tick));
+        let current_tick = self.current_tick;
+        let prev_enemy_ships = self.enemy_ships.clone();
+        self.ship_count = turn.ship_count;
+        for entity in turn.entities {
+            match entity {
+                EntityRecord::Ship {entity_id, x, y, rotation, speed, rum, mine} => {
+                    if mine && !self.my_ships.contains_key(&entity_id) {
+                        self.my_ships_ids.push(entity_id);
+                    }
+                    let ships = if mine {&mut self.my_ships} else {&mut self.enemy_ships};
+                    ships.entry(entity_id).or_insert_with(|| Ship::new(entity_id, x, y, rotation, speed, rum)).
+                        update(current_tick, x, y, rotation, speed, rum);
+                },
+                EntityRecord::Barrel {entity_id, x, y, quantity} => {
+                    self.barrels.entry(entity_id).or_insert_with(|| Barrel::new(entity_id, x, y, quantity)).
+                        keep_alive(current_tick);
+                },
+                EntityRecord::Mine {entity_id, x, y} => {
+                    self.mines.entry(entity_id).or_insert_with(|| Mine::new(entity_id, x, y)).
+                        keep_alive(current_tick);
+                },
+                EntityRecord::Cannonball {entity_id, x, y, owner_id, impact_time} => {
+                    self.cannonballs.entry(entity_id).or_insert_with(|| Cannoball::new(entity_id, owner_id, impact_time, x, y)).
+                        keep_alive(current_tick);
+                },
+            }
+        }
+        self.track_enemies(&prev_enemy_ships);
+        self.observe_enemies(&prev_enemy_ships);
+    }
+
+    fn observe_enemies(&mut self, prev_enemy_ships: &BTreeMap<i32, Ship>) {
+        for (id, prev) in prev_enemy_ships.iter() {
+            let next = &self.enemy_ships[id];
+            if !prev.is_alive(self.current_tick - 1) || !next.is_alive(self.current_tick) {
+                continue;
+            }
+            let fired = self.tracker.fired.contains(id);
+            let mined = self.tracker.mined.contains(id);
+            if let Some(action) = opponent::infer(prev, next, fired, mined) {
+                print_err!("ENEMY {} {}", id, action);
+                self.opponent.observe(*id, action);
+            }
+        }
+    }
+
+    pub fn calc_under_fire(&mut self) {
+        self.impact_field = [Bitboard::default(); IMPACT_TURNS];
+        self.barrels_field.clear();
+        self.mine_field.clear();
+        self.hull_field.clear();
+        for cannonball in self.cannonballs.values() {
+            if !cannonball.is_alive(self.current_tick) {
+                continue;
+            }
+            let turn = (cannonball.impact_time.max(0) as usize).min(IMPACT_TURNS - 1);
+            self.impact_field[turn].insert(&cannonball.target);
+        }
+        let impacts = self.impact_field.iter().fold(Bitboard::default(), |all, turn| all | *turn);
+        for mine in self.mines.values_mut() {
+            if !mine.is_alive(self.current_tick) {
+                continue;
+            }
+            if impacts.contains(&mine.point) {
+                mine.set_under_fire();
+            }
+            self.mine_field.insert(&mine.point);
+        }
+        for barrel in self.barrels.values() {
+            if !barrel.is_alive(self.current_tick) {
+                continue;
+            }
+            self.barrels_field.insert(&barrel.point);
+        }
+        for ship in self.my_ships.values().chain(self.enemy_ships.values()) {
+            if !ship.is_alive(self.current_tick) {
+                continue;
+            }
+            self.hull_field = self.hull_field | Bitboard::hull(&ship.point, ship.rotation);
+        }
+    }
+
+    pub fn get_mine(&self, ship: &Point) -> i32 {  
+        let mut min_distance = 1000;
+        let mut mine_id: i32 = -1;
+        for mine in self.mines.values() {
+            if !mine.is_alive(self.current_tick) {
+                continue;
+            }
+            if mine.under_fire {
+                continue;
+            }
+            let d = ship.distance(&mine.point);
+            if d < min_distance {
+                min_distance = d;
+                mine_id = mine.entity_id;
+            }
+        }
+        mine_id
+    }
+
+    pub fn get_target(&self, ship: &Ship) -> i32 {  
+        let nose = ship.point.get_neighbour(ship.rotation);
+        let next_to = nose.get_neighbour(ship.rotation);
+        let stern = ship.point.get_neighbour((ship.rotation + 3)%6); 
+        for enemy_ship in self.enemy_ships.values() {
+            if !enemy_ship.is_alive(self.current_tick) {
+                continue;
+            }
+            let mut t_pos = enemy_ship.point; 
+            let t = cmp::max(1, enemy_ship.speed);
+            for _ in 0..t {
+                t_pos = t_pos.get_neighbour(enemy_ship.rotation);
+                let sp_nose = t_pos.get_neighbour(enemy_ship.rotation);
+                if (sp_nose == ship.point) || (sp_nose == nose) || (sp_nose == stern) || (next_to == sp_nose) {
+                    return enemy_ship.entity_id;
+                }
+            }
+        }
+        -1
+    }
+
+    pub fn get_closest_target(&self, ship: &Point) -> i32 {  
+        let mut min_distance = 1000;
+        let mut enemy_id: i32 = -1;
+        for enemy_ship in self.enemy_ships.values() {
+            if !enemy_ship.is_alive(self.current_tick) {
+                continue;
+            }
+            let d = ship.distance(&enemy_ship.point);
+            if d < min_distance {
+                min_distance = d;
+                enemy_id = enemy_ship.entity_id;
+            }
+        }
+        enemy_id
+    }
+
+    pub fn waypoints() -> [Point; 4] {
+        [Point::new(3,3), Point::new(19, 3), Point::new(3, 17), Point::new(19, 17)]
+    }
+
+    pub fn get_waypoint(ship: &Ship) -> (Point, usize) {
+        let waypoints = Game::waypoints();
+        let d = ship.point.distance(&waypoints[ship.wp_ind]);
+        let mut wp_ind = ship.wp_ind;
+        if d < 3 {
+            wp_ind = (ship.wp_ind + 1)%4;
+        }
+        (waypoints[wp_ind], wp_ind)
+    }
+
+    pub fn do_next_turn(&mut self) -> Vec<Action> {
+        let decisions = self.strategy.strategy().decide(self);
+        let ids: Vec<i32> = self.my_ships_ids.iter().cloned()
+            .filter(|id| self.my_ships[id].is_alive(self.current_tick))
+            .collect();
+        for (key, decision) in ids.iter().zip(decisions.iter()) {
+            let m_ship = self.my_ships.get_mut(key).unwrap();
+            if let Some(wp_ind) = decision.waypoint {
+                m_ship.wp_ind = wp_ind;
+            }
+            match decision.action {
+                Action::FIRE(_, _) => m_ship.set_cd(COOLDOWN_CANNON),
+                Action::MINE => m_ship.set_mine_cd(COOLDOWN_MINE),
+                _ => {},
+            }
+        }
+        print_err!("TIME {} {:.1}ms of {}ms", self.current_tick,
+                   self.timer.elapsed().as_secs_f64()*1000f64, self.timer.budget().as_millis());
+        decisions.into_iter().map(|decision| decision.action).collect()
+    }
+    
+    pub fn play<R: BufRead>(&mut self, input: &mut R) -> Result<Vec<Action>, ParseError> {
+        self.current_tick += 1;
+        let turn = protocol::read_turn(input, &mut self.lines_read)?;
+        self.load_turn(turn);
+        self.calc_under_fire();
+        Ok(self.do_next_turn())
+    }
+}
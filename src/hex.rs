@@ -0,0 +1,316 @@
+use std::f64;
+use std::f64::consts;
+use std::ops::{Add, Sub};
+
+use board::{Board, OFF_BOARD};
+
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Point {
+        Point {
+            x: x,
+            y: y,
+        }
+    }
+
+    pub fn distance(&self, point: &Point) -> i32 {
+        if self.is_inside() && point.is_inside() {
+            return Board::get().distance(Board::index(self), Board::index(point));
+        }
+        Cube::from(*self).distance(&Cube::from(*point))
+    }
+
+    pub fn is_inside(&self) -> bool {
+        (self.x >= 0) && (self.x <= 22) && (self.y >= 0) && (self.y <= 20)
+    }
+
+    pub fn get_neighbour(&self, rotation: i32) -> Point {
+        if !self.is_inside() {
+            return *self;
+        }
+        match Board::get().neighbour(Board::index(self), rotation) {
+            OFF_BOARD => *self,
+            index => Board::point(index),
+        }
+    }
+
+    // Unlike `get_neighbour` the step may leave the map.
+    pub fn neighbour(&self, rotation: i32) -> Point {
+        if self.is_inside() {
+            let index = Board::get().neighbour(Board::index(self), rotation);
+            if index != OFF_BOARD {
+                return Board::point(index);
+            }
+        }
+        Point::from(Cube::from(*self).neighbour(rotation))
+    }
+
+    // `speed` tiles straight ahead, clamped to the map.
+    pub fn get_offset(&self, rotation: i32, speed: i32) -> Point {
+        let mut point = Point::from(Cube::from(*self).offset(rotation, speed));
+        point.x = point.x.clamp(0, 22);
+        point.y = point.y.clamp(0, 20);
+        point
+    }
+
+    pub fn angle(&self, target: &Point) -> f64 {
+        let dy = ((target.y - self.y) as f64) * f64::sqrt(3f64) / 2f64;
+        let dx = (target.x - self.x) as f64 + (((self.y - target.y) & 1) as f64) * 0.5f64;
+        let mut angle = -f64::atan2(dy, dx) * 3f64 / consts::PI;
+        if angle < 0f64 {
+            angle = angle + 6f64;
+        } else if angle >= 6f64 {
+            angle = angle - 6f64;
+        }
+        angle
+    }
+}
+
+// Cube coordinates of a tile, x + y + z == 0. Rows of `Point` are "odd-r" offset: odd rows are
+// shifted half a tile to the right.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
+pub struct Cube {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+// Indexed by ship rotation, 0 is east and the others follow counterclockwise.
+const DIRECTIONS: [Cube; 6] = [
+    Cube {x: 1, y: -1, z: 0},
+    Cube {x: 1, y: 0, z: -1},
+    Cube {x: 0, y: 1, z: -1},
+    Cube {x: -1, y: 1, z: 0},
+    Cube {x: -1, y: 0, z: 1},
+    Cube {x: 0, y: -1, z: 1},
+];
+
+impl From<Point> for Cube {
+    fn from(point: Point) -> Cube {
+        let x = point.x - (point.y - (point.y & 1))/2;
+        let z = point.y;
+        Cube {
+            x: x,
+            y: -(x + z),
+            z: z,
+        }
+    }
+}
+
+impl From<Cube> for Point {
+    fn from(cube: Cube) -> Point {
+        Point {
+            x: cube.x + (cube.z - (cube.z & 1))/2,
+            y: cube.z,
+        }
+    }
+}
+
+impl Add for Cube {
+    type Output = Cube;
+
+    fn add(self, other: Cube) -> Cube {
+        Cube::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Cube {
+    type Output = Cube;
+
+    fn sub(self, other: Cube) -> Cube {
+        Cube::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Cube {
+    pub fn new(x: i32, y: i32, z: i32) -> Cube {
+        Cube {
+            x: x,
+            y: y,
+            z: z,
+        }
+    }
+
+    pub fn direction(rotation: i32) -> Cube {
+        DIRECTIONS[rotation.rem_euclid(6) as usize]
+    }
+
+    pub fn scale(&self, factor: i32) -> Cube {
+        Cube::new(self.x*factor, self.y*factor, self.z*factor)
+    }
+
+    pub fn distance(&self, other: &Cube) -> i32 {
+        ((self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs())/2
+    }
+
+    pub fn neighbour(&self, rotation: i32) -> Cube {
+        *self + Cube::direction(rotation)
+    }
+
+    pub fn offset(&self, rotation: i32, distance: i32) -> Cube {
+        *self + Cube::direction(rotation).scale(distance)
+    }
+
+    // Rotated by `steps` sixths of a turn counterclockwise around `center`.
+    pub fn rotate(&self, center: &Cube, steps: i32) -> Cube {
+        let mut v = *self - *center;
+        for _ in 0..steps.rem_euclid(6) {
+            v = Cube::new(-v.y, -v.z, -v.x);
+        }
+        *center + v
+    }
+
+    // Tiles crossed by the straight line from `self` to `other`, both ends included.
+    pub fn line(&self, other: &Cube) -> Vec<Cube> {
+        let n = self.distance(other);
+        if n == 0 {
+            return vec![*self];
+        }
+        // The nudge keeps points exactly on a tile border from rounding both ways.
+        let (ax, ay, az) = (self.x as f64 + 1e-6, self.y as f64 + 1e-6, self.z as f64 - 2e-6);
+        let (bx, by, bz) = (other.x as f64 + 1e-6, other.y as f64 + 1e-6, other.z as f64 - 2e-6);
+        (0..(n + 1)).map(|i| {
+            let t = (i as f64)/(n as f64);
+            Cube::round(ax + (bx - ax)*t, ay + (by - ay)*t, az + (bz - az)*t)
+        }).collect()
+    }
+
+    // Tiles exactly `radius` away, starting from the rotation 4 corner.
+    pub fn ring(&self, radius: i32) -> Vec<Cube> {
+        if radius == 0 {
+            return vec![*self];
+        }
+        let mut tiles = Vec::new();
+        let mut cube = self.offset(4, radius);
+        for rotation in 0..6 {
+            for _ in 0..radius {
+                tiles.push(cube);
+                cube = cube.neighbour(rotation);
+            }
+        }
+        tiles
+    }
+
+    // Tiles at most `radius` away.
+    pub fn range(&self, radius: i32) -> Vec<Cube> {
+        let mut tiles = Vec::new();
+        for dx in -radius..(radius + 1) {
+            for dy in (-radius).max(-dx - radius)..(radius.min(-dx + radius) + 1) {
+                tiles.push(Cube::new(self.x + dx, self.y + dy, self.z - dx - dy));
+            }
+        }
+        tiles
+    }
+
+    fn round(x: f64, y: f64, z: f64) -> Cube {
+        let (mut rx, mut ry, mut rz) = (x.round(), y.round(), z.round());
+        let (dx, dy, dz) = ((rx - x).abs(), (ry - y).abs(), (rz - z).abs());
+        if (dx > dy) && (dx > dz) {
+            rx = -ry - rz;
+        } else if dy > dz {
+            ry = -rx - rz;
+        } else {
+            rz = -rx - ry;
+        }
+        Cube::new(rx as i32, ry as i32, rz as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Neighbour offsets of the original parity tables, by rotation.
+    const EVEN_ROW: [(i32, i32); 6] = [(1, 0), (0, -1), (-1, -1), (-1, 0), (-1, 1), (0, 1)];
+    const ODD_ROW: [(i32, i32); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (0, 1), (1, 1)];
+
+    fn tiles() -> Vec<Point> {
+        (0..21).flat_map(|y| (0..23).map(move |x| Point::new(x, y))).collect()
+    }
+
+    #[test]
+    fn point_cube_round_trip() {
+        for point in tiles() {
+            let cube = Cube::from(point);
+            assert_eq!(cube.x + cube.y + cube.z, 0);
+            assert_eq!(Point::from(cube), point);
+        }
+    }
+
+    #[test]
+    fn neighbour_matches_parity_tables() {
+        for point in tiles() {
+            let table = if point.y%2 == 0 {EVEN_ROW} else {ODD_ROW};
+            for rotation in 0..6 {
+                let (dx, dy) = table[rotation as usize];
+                let expected = Point::new(point.x + dx, point.y + dy);
+                assert_eq!(point.neighbour(rotation), expected);
+                assert_eq!(point.distance(&expected), 1);
+                let clamped = if expected.is_inside() {expected} else {point};
+                assert_eq!(point.get_neighbour(rotation), clamped);
+            }
+        }
+    }
+
+    #[test]
+    fn offset_is_repeated_neighbours() {
+        for &start in [Point::new(10, 10), Point::new(10, 11)].iter() {
+            for rotation in 0..6 {
+                let mut point = start;
+                for speed in 1..4 {
+                    point = point.neighbour(rotation);
+                    assert_eq!(start.get_offset(rotation, speed), point);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn offset_backwards_covers_the_whole_distance() {
+        // Rotation 3 used to move only half the distance.
+        assert_eq!(Point::new(10, 10).get_offset(3, 2), Point::new(8, 10));
+        assert_eq!(Point::new(10, 11).get_offset(3, 3), Point::new(7, 11));
+        assert_eq!(Point::new(1, 4).get_offset(3, 2), Point::new(0, 4));
+    }
+
+    #[test]
+    fn ring_and_range_sizes() {
+        let center = Cube::from(Point::new(11, 10));
+        for radius in 0..5 {
+            let ring = center.ring(radius);
+            assert_eq!(ring.len() as i32, if radius == 0 {1} else {6*radius});
+            assert!(ring.iter().all(|cube| cube.distance(&center) == radius));
+            let range = center.range(radius);
+            assert_eq!(range.len() as i32, 3*radius*(radius + 1) + 1);
+            assert!(range.iter().all(|cube| (cube.distance(&center) <= radius) && (cube.x + cube.y + cube.z == 0)));
+        }
+    }
+
+    #[test]
+    fn rotate_turns_counterclockwise() {
+        let center = Cube::from(Point::new(7, 8));
+        for rotation in 0..6 {
+            for steps in -6..7 {
+                let rotated = center.neighbour(rotation).rotate(&center, steps);
+                assert_eq!(rotated, center.neighbour(rotation + steps));
+            }
+        }
+        let far = Cube::from(Point::new(12, 3));
+        assert_eq!(far.rotate(&center, 2).distance(&center), far.distance(&center));
+        assert_eq!(far.rotate(&center, 6), far);
+    }
+
+    #[test]
+    fn line_steps_between_neighbours() {
+        let (a, b) = (Cube::from(Point::new(2, 3)), Cube::from(Point::new(15, 12)));
+        let line = a.line(&b);
+        assert_eq!(line.len() as i32, a.distance(&b) + 1);
+        assert_eq!((line[0], line[line.len() - 1]), (a, b));
+        assert!(line.windows(2).all(|w| w[0].distance(&w[1]) == 1));
+    }
+}
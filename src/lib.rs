@@ -0,0 +1,40 @@
+use std::sync::atomic::AtomicBool;
+
+// Debug output of the bot, switched off when many games run in one process.
+pub static LOG: AtomicBool = AtomicBool::new(true);
+
+macro_rules! print_err {
+    ($($arg:tt)*) => (
+        if $crate::LOG.load(::std::sync::atomic::Ordering::Relaxed) {
+            use std::io::Write;
+            writeln!(&mut ::std::io::stderr(), $($arg)*).ok();
+        }
+    )
+}
+
+pub mod action;
+pub mod arena;
+pub mod board;
+pub mod coordinator;
+pub mod endgame;
+pub mod entities;
+pub mod game;
+pub mod hex;
+pub mod mining;
+pub mod opponent;
+pub mod params;
+pub mod phase;
+pub mod planner;
+pub mod protocol;
+pub mod random;
+pub mod referee;
+pub mod replay;
+pub mod routing;
+pub mod sacrifice;
+pub mod strategy;
+pub mod targeting;
+pub mod timer;
+pub mod tracker;
+
+pub use action::Action;
+pub use game::Game;
@@ -0,0 +1,56 @@
+use std::cmp;
+
+use entities::Ship;
+use game::Game;
+use hex::Point;
+
+// How many turns ahead a dropped mine is expected to stay relevant.
+const MINE_HORIZON: i32 = 4;
+
+// Tiles covered by the hull of `ship` if it keeps its heading for `turns` turns at its
+// speed (at least `min_speed`). Every intermediate step is included, mines trigger on the way.
+pub fn track(ship: &Ship, turns: i32, min_speed: i32) -> Vec<Point> {
+    let speed = cmp::max(ship.speed, min_speed);
+    let mut point = ship.point;
+    let mut tiles = vec![point.neighbour((ship.rotation + 3)%6), point, point.neighbour(ship.rotation)];
+    for _ in 0..turns*speed {
+        let next = point.neighbour(ship.rotation);
+        if !next.is_inside() {
+            break;
+        }
+        point = next;
+        tiles.push(point.neighbour(ship.rotation));
+    }
+    tiles
+}
+
+impl Game {
+    // The tile a MINE order of `ship` drops the mine on, right behind its stern.
+    pub fn mine_target(ship: &Ship) -> Point {
+        ship.point.neighbour((ship.rotation + 3)%6).neighbour((ship.rotation + 3)%6)
+    }
+
+    pub fn should_mine(&self, ship: &Ship) -> bool {
+        if (ship.mine_cd > 0) || (ship.speed == 0) {
+            return false;
+        }
+        let target = Game::mine_target(ship);
+        if !target.is_inside() || self.barrels_field.contains(&target) || self.mine_field.contains(&target) {
+            return false;
+        }
+        // The referee refuses a mine on a tile a ship is standing on.
+        if self.hull_field.contains(&target) {
+            return false;
+        }
+        let alive = |other: &&Ship| other.is_alive(self.current_tick);
+        for other in self.my_ships.values().filter(alive) {
+            if (other.entity_id != ship.entity_id) && track(other, MINE_HORIZON, 0).contains(&target) {
+                return false;
+            }
+        }
+        if self.enemy_ships.values().filter(alive).any(|enemy_ship| track(enemy_ship, MINE_HORIZON, 1).contains(&target)) {
+            return true;
+        }
+        false
+    }
+}
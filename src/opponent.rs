@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+
+use action::Action;
+use entities::Ship;
+use referee;
+use targeting::MOVES;
+
+// WAIT, FASTER, SLOWER, PORT, STARBOARD as in `MOVES`, then FIRE and MINE.
+const KINDS: usize = 7;
+const FIRE: usize = 5;
+const MINE: usize = 6;
+// Pseudo-observations of every move, the model starts uniform and drifts with the data.
+const PRIOR: f64 = 1f64;
+// Weight of the fleet-wide habits in a single ship's model, in observations.
+const SHIP_PRIOR: f64 = 5f64;
+
+fn kind(action: &Action) -> usize {
+    match *action {
+        Action::FIRE(_, _) => FIRE,
+        Action::MINE => MINE,
+        action => MOVES.iter().position(|&m| m == action).unwrap(),
+    }
+}
+
+// What the enemy did, worked out from two consecutive turns.
+#[derive(Default, Clone)]
+pub struct OpponentModel {
+    // Action counts over all enemy ships, and per enemy ship.
+    pub counts: [u32; KINDS],
+    pub ship_counts: BTreeMap<i32, [u32; KINDS]>,
+}
+
+// The order `prev` gave to become `next`. `fired` and `mined` tell whether a cannonball or a
+// mine of this ship showed up, those orders move the ship like WAIT. None when the ship was
+// stopped by a collision and the move cannot be told.
+pub fn infer(prev: &Ship, next: &Ship, fired: bool, mined: bool) -> Option<Action> {
+    let action = *MOVES.iter().find(|&&action| {
+        referee::move_alone(prev.point, prev.rotation, prev.speed, action) == (next.point, next.rotation, next.speed)
+    })?;
+    if action == Action::WAIT {
+        if fired {
+            return Some(Action::FIRE(-1, -1));
+        }
+        if mined {
+            return Some(Action::MINE);
+        }
+    }
+    Some(action)
+}
+
+// `weights` plus the `counts` of every move.
+fn moves(mut weights: [f64; 5], counts: &[u32; KINDS]) -> [f64; 5] {
+    for (i, weight) in weights.iter_mut().enumerate() {
+        *weight += counts[i] as f64;
+    }
+    weights[0] += (counts[FIRE] + counts[MINE]) as f64;
+    weights
+}
+
+fn normalize(weights: [f64; 5]) -> [f64; 5] {
+    let total: f64 = weights.iter().sum();
+    let mut probabilities = [0f64; 5];
+    for (p, weight) in probabilities.iter_mut().zip(weights.iter()) {
+        *p = weight/total;
+    }
+    probabilities
+}
+
+impl OpponentModel {
+    pub fn observe(&mut self, ship_id: i32, action: Action) {
+        self.counts[kind(&action)] += 1;
+        self.ship_counts.entry(ship_id).or_insert([0; KINDS])[kind(&action)] += 1;
+    }
+
+    // Chance of each of `MOVES` for the next order of enemy ship `ship_id`: its own orders
+    // on top of the habits of the whole fleet. FIRE and MINE count as WAIT since the ship
+    // keeps going the same way.
+    pub fn move_probabilities(&self, ship_id: i32) -> [f64; 5] {
+        let fleet = normalize(moves([PRIOR; 5], &self.counts));
+        let mut prior = fleet;
+        for weight in prior.iter_mut() {
+            *weight *= SHIP_PRIOR;
+        }
+        match self.ship_counts.get(&ship_id) {
+            Some(counts) => normalize(moves(prior, counts)),
+            None => fleet,
+        }
+    }
+
+    // Speed the ship most likely has after its next order.
+    pub fn likely_speed(&self, ship: &Ship) -> i32 {
+        let probabilities = self.move_probabilities(ship.entity_id);
+        let best = (0..MOVES.len()).fold(0, |best, m| if probabilities[m] > probabilities[best] {m} else {best});
+        referee::move_alone(ship.point, ship.rotation, ship.speed, MOVES[best]).2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ships_keep_their_own_habits() {
+        let mut model = OpponentModel::default();
+        for _ in 0..10 {
+            model.observe(1, Action::FASTER);
+            model.observe(2, Action::PORT);
+        }
+        let (first, second) = (model.move_probabilities(1), model.move_probabilities(2));
+        assert!(first[1] > first[3]);
+        assert!(second[3] > second[1]);
+        // An unseen ship follows the fleet, where both moves are as common.
+        let unseen = model.move_probabilities(3);
+        assert!((unseen[1] - unseen[3]).abs() < 1e-9);
+        assert!((unseen.iter().sum::<f64>() - 1f64).abs() < 1e-9);
+    }
+}
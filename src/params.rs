@@ -0,0 +1,173 @@
+use std::env;
+use std::fs;
+
+// Weights of `Game::check_position` and `Game::move_recur`, then the `plan_` ones of the beam
+// and MCTS planners' `planner::evaluate`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvalParams {
+    pub barrel_tile: i32,
+    pub mine_tile: i32,
+    pub impact_center: i32,
+    pub impact_end: i32,
+    pub closer: i32,
+    pub straighter: i32,
+    pub edge: i32,
+    pub stopped: i32,
+    pub moving: i32,
+    // Later turns of the search count `discount_num/discount_den` of the turn before.
+    pub discount_num: i32,
+    pub discount_den: i32,
+    pub depth: i32,
+    pub plan_rum: i32,
+    pub plan_distance: i32,
+    pub plan_stopped: i32,
+    pub plan_edge: i32,
+    pub plan_impact_center: i32,
+    pub plan_impact_end: i32,
+}
+
+impl Default for EvalParams {
+    fn default() -> EvalParams {
+        EvalParams {
+            barrel_tile: 10,
+            mine_tile: -25,
+            impact_center: -50,
+            impact_end: -25,
+            closer: 1,
+            straighter: 1,
+            edge: -1,
+            stopped: -1,
+            moving: 1,
+            discount_num: 2,
+            discount_den: 3,
+            depth: 3,
+            plan_rum: 1,
+            plan_distance: -1,
+            plan_stopped: -1,
+            plan_edge: -1,
+            plan_impact_center: -50,
+            plan_impact_end: -25,
+        }
+    }
+}
+
+pub const ENV_FILE: &str = "COTC_PARAMS";
+// Single weights are overridden by `COTC_<NAME>`, e.g. COTC_MINE_TILE=-40.
+const ENV_PREFIX: &str = "COTC_";
+
+impl EvalParams {
+    pub fn names() -> [&'static str; 18] {
+        ["barrel_tile", "mine_tile", "impact_center", "impact_end", "closer", "straighter",
+         "edge", "stopped", "moving", "discount_num", "discount_den", "depth",
+         "plan_rum", "plan_distance", "plan_stopped", "plan_edge", "plan_impact_center", "plan_impact_end"]
+    }
+
+    pub fn field(&mut self, name: &str) -> Option<&mut i32> {
+        match name {
+            "barrel_tile" => Some(&mut self.barrel_tile),
+            "mine_tile" => Some(&mut self.mine_tile),
+            "impact_center" => Some(&mut self.impact_center),
+            "impact_end" => Some(&mut self.impact_end),
+            "closer" => Some(&mut self.closer),
+            "straighter" => Some(&mut self.straighter),
+            "edge" => Some(&mut self.edge),
+            "stopped" => Some(&mut self.stopped),
+            "moving" => Some(&mut self.moving),
+            "discount_num" => Some(&mut self.discount_num),
+            "discount_den" => Some(&mut self.discount_den),
+            "depth" => Some(&mut self.depth),
+            "plan_rum" => Some(&mut self.plan_rum),
+            "plan_distance" => Some(&mut self.plan_distance),
+            "plan_stopped" => Some(&mut self.plan_stopped),
+            "plan_edge" => Some(&mut self.plan_edge),
+            "plan_impact_center" => Some(&mut self.plan_impact_center),
+            "plan_impact_end" => Some(&mut self.plan_impact_end),
+            _ => None,
+        }
+    }
+
+    // Smallest value the search can run with, `discount_den` divides and `depth` counts turns.
+    fn minimum(name: &str) -> i32 {
+        match name {
+            "discount_den" | "depth" => 1,
+            _ => i32::MIN,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<i32> {
+        self.clone().field(name).map(|value| *value)
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let value = value.trim().parse::<i32>().map_err(|_| format!("{}: '{}' is not a number", name, value.trim()))?;
+        if value < EvalParams::minimum(name) {
+            return Err(format!("{}: must be at least {}", name, EvalParams::minimum(name)));
+        }
+        let field = self.field(name).ok_or_else(|| format!("unknown parameter '{}'", name))?;
+        *field = value;
+        Ok(())
+    }
+
+    // Reads `name = value` lines (TOML) or a flat `{"name": value, ...}` object (JSON), only
+    // the names given are changed.
+    pub fn parse(&mut self, text: &str) -> Result<(), String> {
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.starts_with('[') {
+                continue;
+            }
+            for entry in line.trim_matches(['{', '}']).split(',') {
+                let entry = entry.trim();
+                if entry.is_empty() {
+                    continue;
+                }
+                let mut parts = entry.splitn(2, ['=', ':']);
+                let name = parts.next().unwrap().trim().trim_matches('"');
+                let value = parts.next().ok_or_else(|| format!("line {}: expected 'name = value'", i + 1))?;
+                self.set(name, value).map_err(|e| format!("line {}: {}", i + 1, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    // Defaults, then the file named by COTC_PARAMS, then the COTC_<NAME> variables.
+    pub fn from_env() -> Result<EvalParams, String> {
+        let mut params = EvalParams::default();
+        if let Ok(path) = env::var(ENV_FILE) {
+            let text = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path, e))?;
+            params.parse(&text).map_err(|e| format!("{}: {}", path, e))?;
+        }
+        for name in EvalParams::names().iter() {
+            let var = format!("{}{}", ENV_PREFIX, name.to_uppercase());
+            if let Ok(value) = env::var(&var) {
+                params.set(name, &value).map_err(|e| format!("{}: {}", var, e))?;
+            }
+        }
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_toml_and_json() {
+        let mut params = EvalParams::default();
+        params.parse("[weights]\nmine_tile = -40 # deeper\nclose = 2\n").err().unwrap();
+        params.parse("mine_tile = -40\n").unwrap();
+        params.parse("{\"edge\": -3, \"depth\": 2}").unwrap();
+        assert_eq!((params.mine_tile, params.edge, params.depth), (-40, -3, 2));
+    }
+
+    #[test]
+    fn rejects_values_the_search_cannot_use() {
+        let mut params = EvalParams::default();
+        assert!(params.set("discount_den", "0").is_err());
+        assert!(params.set("depth", "0").is_err());
+        assert!(params.parse("discount_den = -3").is_err());
+        assert_eq!(params, EvalParams::default());
+        params.set("depth", "1").unwrap();
+        assert_eq!(params.depth, 1);
+    }
+}
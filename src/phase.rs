@@ -0,0 +1,106 @@
+use entities::Ship;
+use game::Game;
+use hex::Point;
+use referee::{self, MAX_TURNS};
+
+// From here on the rum difference decides how the bot plays.
+const LATE_TURNS: i32 = 50;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Phase {
+    // Collect as many barrels as possible.
+    Race,
+    // Ahead near the end: keep away from the enemy.
+    Lead,
+    // Behind near the end: shoot at anything that might hit.
+    AllIn,
+}
+
+impl Game {
+    pub fn turns_left(&self) -> i32 {
+        MAX_TURNS - self.current_tick
+    }
+
+    pub fn enemy_ship_count(&self) -> i32 {
+        self.enemy_ships.values().filter(|enemy_ship| enemy_ship.is_alive(self.current_tick)).count() as i32
+    }
+
+    // Every ship loses one rum a turn, so the side with more ships left needs a wider lead to
+    // still be ahead when the game ends.
+    pub fn phase(&self) -> Phase {
+        if self.turns_left() > LATE_TURNS {
+            return Phase::Race;
+        }
+        let (my_rum, enemy_rum) = referee::total_rum(self);
+        let decay = (self.ship_count - self.enemy_ship_count())*self.turns_left();
+        if my_rum - enemy_rum > decay {
+            Phase::Lead
+        } else {
+            Phase::AllIn
+        }
+    }
+
+    // The patrol waypoint farthest from the enemy ships, skipping the one `ship` has just reached.
+    pub fn safe_waypoint(&self, ship: &Ship) -> (Point, usize) {
+        let waypoints = Game::waypoints();
+        let enemy_distance = |point: &Point| self.enemy_ships.values()
+            .filter(|enemy_ship| enemy_ship.is_alive(self.current_tick))
+            .map(|enemy_ship| enemy_ship.point.distance(point))
+            .min()
+            .unwrap_or(0);
+        let wp_ind = (0..waypoints.len())
+            .filter(|&i| ship.point.distance(&waypoints[i]) >= 3)
+            .max_by_key(|&i| (enemy_distance(&waypoints[i]), -(i as i32)))
+            .unwrap_or(ship.wp_ind);
+        (waypoints[wp_ind], wp_ind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `mine` and `enemy` are the rum of each ship, 30 turns before the end.
+    fn game(mine: &[i32], enemy: &[i32]) -> Game {
+        let mut game = Game {current_tick: MAX_TURNS - 30, ..Game::default()};
+        for (i, &rum) in mine.iter().enumerate() {
+            let mut ship = Ship::new(2*i as i32, 5, 2 + 4*i as i32, 0, 0, rum);
+            ship.tick_accessed = game.current_tick;
+            game.my_ships_ids.push(ship.entity_id);
+            game.my_ships.insert(ship.entity_id, ship);
+        }
+        for (i, &rum) in enemy.iter().enumerate() {
+            let mut ship = Ship::new(2*i as i32 + 1, 17, 2 + 4*i as i32, 3, 0, rum);
+            ship.tick_accessed = game.current_tick;
+            game.enemy_ships.insert(ship.entity_id, ship);
+        }
+        game.ship_count = mine.len() as i32;
+        game
+    }
+
+    #[test]
+    fn early_turns_race() {
+        let mut game = game(&[50], &[90]);
+        game.current_tick = 0;
+        assert_eq!(game.phase(), Phase::Race);
+    }
+
+    #[test]
+    fn rum_decides_between_even_fleets() {
+        assert_eq!(game(&[60, 60], &[50, 50]).phase(), Phase::Lead);
+        assert_eq!(game(&[50, 50], &[60, 60]).phase(), Phase::AllIn);
+    }
+
+    #[test]
+    fn bigger_fleet_needs_a_wider_lead() {
+        // 20 rum ahead with one ship more loses 30 of it by the end.
+        assert_eq!(game(&[60, 60], &[100]).phase(), Phase::AllIn);
+        assert_eq!(game(&[80, 60], &[100]).phase(), Phase::Lead);
+    }
+
+    #[test]
+    fn smaller_fleet_can_lead_while_behind() {
+        assert_eq!(game(&[100], &[60, 50]).phase(), Phase::Lead);
+        assert_eq!(game(&[70], &[60, 50]).phase(), Phase::AllIn);
+    }
+}
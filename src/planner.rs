@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::f64;
+
+use action::Action;
+use game::Game;
+use random::Random;
+use referee;
+use targeting::MOVES;
+use timer::Timer;
+
+const DISCOUNT: f64 = 2f64/3f64;
+const EXPLORATION: f64 = 1.4;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PlannerKind {
+    // The per-ship fixed-depth `Game::move_recur` tree.
+    Tree,
+    Beam,
+    Mcts,
+}
+
+impl PlannerKind {
+    pub fn parse(name: &str) -> Option<PlannerKind> {
+        match name {
+            "tree" => Some(PlannerKind::Tree),
+            "beam" => Some(PlannerKind::Beam),
+            "mcts" => Some(PlannerKind::Mcts),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Planner {
+    pub kind: PlannerKind,
+    pub depth: usize,
+    pub beam_width: usize,
+}
+
+impl Default for Planner {
+    fn default() -> Planner {
+        Planner {
+            kind: PlannerKind::Tree,
+            depth: 4,
+            beam_width: 12,
+        }
+    }
+}
+
+struct BeamNode {
+    state: Game,
+    first: Vec<usize>,
+    value: f64,
+}
+
+struct MctsNode {
+    state: Game,
+    // Per ship, per move: (value sum, visits). Ships choose independently (decoupled UCT).
+    stats: Vec<[(f64, u32); 5]>,
+    visits: u32,
+    children: HashMap<Vec<usize>, usize>,
+}
+
+impl MctsNode {
+    fn new(state: Game, ships: usize) -> MctsNode {
+        MctsNode {
+            state: state,
+            stats: vec![[(0f64, 0); 5]; ships],
+            visits: 0,
+            children: HashMap::new(),
+        }
+    }
+}
+
+// Score of a position for our ships, along the lines of `Game::check_position`: rum first,
+// then getting closer to the barrel (or waypoint) and keeping clear of borders and of the
+// cannonballs about to land. The weights are the `plan_` ones of `state.params`.
+pub fn evaluate(state: &Game) -> f64 {
+    let params = &state.params;
+    let mut value = 0;
+    for ship in state.my_ships.values() {
+        if !ship.is_alive(state.current_tick) {
+            continue;
+        }
+        value = value + params.plan_rum*ship.rum;
+        let barrel = state.barrels.values()
+            .filter(|barrel| barrel.is_alive(state.current_tick))
+            .min_by_key(|barrel| ship.point.distance(&barrel.point));
+        let dest = match barrel {
+            Some(barrel) => barrel.point,
+            None => Game::get_waypoint(ship).0,
+        };
+        value = value + params.plan_distance*ship.point.distance(&dest);
+        if ship.speed == 0 {
+            value = value + params.plan_stopped;
+        }
+        let (point, rotation) = (ship.point, ship.rotation);
+        if (point.x <= 1) && ((rotation == 3) || (rotation == 2) || (rotation == 4)) {
+            value = value + params.plan_edge;
+        }
+        if (point.y <= 1) && ((rotation == 1) || (rotation == 2)) {
+            value = value + params.plan_edge;
+        }
+        if (point.x >= 21) && ((rotation == 0) || (rotation == 1) || (rotation == 5)) {
+            value = value + params.plan_edge;
+        }
+        if (point.y >= 19) && ((rotation == 4) || (rotation == 5)) {
+            value = value + params.plan_edge;
+        }
+        for cannonball in state.cannonballs.values() {
+            if !cannonball.is_alive(state.current_tick) || (cannonball.impact_time > 0) {
+                continue;
+            }
+            if cannonball.target == point {
+                value = value + params.plan_impact_center;
+            } else if (cannonball.target == point.neighbour(rotation)) ||
+                (cannonball.target == point.neighbour((rotation + 3)%6)) {
+                value = value + params.plan_impact_end;
+            }
+        }
+    }
+    value as f64
+}
+
+// Index of the best of `values`, the first one on ties so that WAIT wins those.
+fn first_best<T: PartialOrd>(values: &[T]) -> usize {
+    (0..values.len()).fold(0, |best, i| if values[i] > values[best] {i} else {best})
+}
+
+fn joint_actions(ships: usize) -> Vec<Vec<usize>> {
+    let mut result = vec![Vec::new()];
+    for _ in 0..ships {
+        result = result.into_iter()
+            .flat_map(|prefix| (0..MOVES.len()).map(move |m| {
+                let mut next = prefix.clone();
+                next.push(m);
+                next
+            }))
+            .collect();
+    }
+    result
+}
+
+fn step(state: &Game, ids: &[i32], moves: &[usize]) -> Game {
+    let actions: HashMap<i32, Action> = ids.iter().cloned().zip(moves.iter().map(|&m| MOVES[m])).collect();
+    referee::next_turn(state, &actions)
+}
+
+impl Planner {
+    // Best first move of every alive ship of ours, searched jointly until the turn timer runs out.
+    // Empty when the search had no time to evaluate a single move.
+    pub fn plan(&self, game: &Game, timer: &Timer) -> HashMap<i32, Action> {
+        let ids: Vec<i32> = game.my_ships_ids.iter().cloned()
+            .filter(|id| game.my_ships[id].is_alive(game.current_tick))
+            .collect();
+        let moves = match self.kind {
+            PlannerKind::Tree => None,
+            PlannerKind::Beam => self.beam(game, &ids, timer),
+            PlannerKind::Mcts => self.mcts(game, &ids, timer),
+        };
+        match moves {
+            Some(moves) => ids.into_iter().zip(moves.into_iter().map(|m| MOVES[m])).collect(),
+            None => HashMap::new(),
+        }
+    }
+
+    fn beam(&self, game: &Game, ids: &[i32], timer: &Timer) -> Option<Vec<usize>> {
+        let joints = joint_actions(ids.len());
+        let mut nodes = vec![BeamNode {state: game.clone(), first: Vec::new(), value: 0f64}];
+        let mut best = None;
+        let mut weight = 1f64;
+        for _ in 0..self.depth {
+            let mut children: Vec<BeamNode> = Vec::new();
+            let mut complete = true;
+            'layer: for node in nodes.iter() {
+                for joint in joints.iter() {
+                    if timer.is_over() {
+                        complete = false;
+                        break 'layer;
+                    }
+                    let state = step(&node.state, ids, joint);
+                    let value = node.value + weight*evaluate(&state);
+                    let first = if node.first.is_empty() {joint.clone()} else {node.first.clone()};
+                    children.push(BeamNode {state: state, first: first, value: value});
+                }
+            }
+            // A cut layer only says something when no full one came before it.
+            if !complete && best.is_some() {
+                break;
+            }
+            let values: Vec<f64> = children.iter().map(|child| child.value).collect();
+            if !children.is_empty() {
+                best = Some(children[first_best(&values)].first.clone());
+            }
+            if !complete {
+                break;
+            }
+            children.sort_by(|a, b| b.value.partial_cmp(&a.value).unwrap());
+            children.truncate(self.beam_width);
+            nodes = children;
+            weight *= DISCOUNT;
+        }
+        best
+    }
+
+    fn mcts(&self, game: &Game, ids: &[i32], timer: &Timer) -> Option<Vec<usize>> {
+        let ships = ids.len();
+        let mut random = Random::new(game.current_tick as u64);
+        let mut nodes = vec![MctsNode::new(game.clone(), ships)];
+        let (mut low, mut high) = (f64::MAX, f64::MIN);
+        while !timer.is_over() {
+            let mut path: Vec<(usize, Vec<usize>)> = Vec::new();
+            let mut current = 0;
+            let mut value = 0f64;
+            let mut weight = 1f64;
+            for depth in 0..self.depth {
+                let moves: Vec<usize> = (0..ships).map(|s| {
+                    let node = &nodes[current];
+                    let range = if high > low {high - low} else {1f64};
+                    (0..MOVES.len()).max_by(|&a, &b| {
+                        let ucb = |m: usize| {
+                            let (sum, n) = node.stats[s][m];
+                            if n == 0 {
+                                return f64::MAX;
+                            }
+                            (sum/(n as f64) - low)/range + EXPLORATION*((node.visits.max(1) as f64).ln()/(n as f64)).sqrt()
+                        };
+                        ucb(a).partial_cmp(&ucb(b)).unwrap()
+                    }).unwrap()
+                }).collect();
+                path.push((current, moves.clone()));
+                let child = nodes[current].children.get(&moves).cloned();
+                let next = match child {
+                    Some(child) => child,
+                    None => {
+                        let state = step(&nodes[current].state, ids, &moves);
+                        nodes.push(MctsNode::new(state, ships));
+                        let child = nodes.len() - 1;
+                        nodes[current].children.insert(moves, child);
+                        value += weight*evaluate(&nodes[child].state);
+                        weight *= DISCOUNT;
+                        // Random rollout from the new node down to the search depth.
+                        let mut state = nodes[child].state.clone();
+                        for _ in (depth + 1)..self.depth {
+                            let moves: Vec<usize> = (0..ships).map(|_| random.next_int(MOVES.len() as i32) as usize).collect();
+                            state = step(&state, ids, &moves);
+                            value += weight*evaluate(&state);
+                            weight *= DISCOUNT;
+                        }
+                        break;
+                    },
+                };
+                current = next;
+                value += weight*evaluate(&nodes[current].state);
+                weight *= DISCOUNT;
+            }
+            low = low.min(value);
+            high = high.max(value);
+            for (node, moves) in path {
+                nodes[node].visits += 1;
+                for (s, &m) in moves.iter().enumerate() {
+                    nodes[node].stats[s][m].0 += value;
+                    nodes[node].stats[s][m].1 += 1;
+                }
+            }
+        }
+        if nodes[0].visits == 0 {
+            return None;
+        }
+        Some((0..ships).map(|s| {
+            let visits: Vec<u32> = nodes[0].stats[s].iter().map(|&(_, n)| n).collect();
+            first_best(&visits)
+        }).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use entities::{Barrel, Ship};
+    use referee::MAX_SHIP_RUM;
+    use super::*;
+
+    fn game() -> Game {
+        let mut game = Game::default();
+        for &(id, x) in [(0, 5), (2, 9)].iter() {
+            game.my_ships_ids.push(id);
+            game.my_ships.insert(id, Ship::new(id, x, 10, 0, 1, MAX_SHIP_RUM));
+        }
+        game.enemy_ships.insert(1, Ship::new(1, 18, 3, 3, 1, MAX_SHIP_RUM));
+        game.barrels.insert(3, Barrel::new(3, 12, 14, 15));
+        game
+    }
+
+    fn timer(ms: u64) -> Timer {
+        let mut timer = Timer::default();
+        timer.start_at(Instant::now(), Duration::from_millis(ms));
+        timer
+    }
+
+    #[test]
+    fn expired_timer_gives_no_plan() {
+        for &kind in [PlannerKind::Beam, PlannerKind::Mcts].iter() {
+            let planner = Planner {kind: kind, ..Planner::default()};
+            assert!(planner.plan(&game(), &timer(0)).is_empty(), "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn search_plans_every_ship() {
+        for &kind in [PlannerKind::Beam, PlannerKind::Mcts].iter() {
+            let planner = Planner {kind: kind, depth: 2, ..Planner::default()};
+            let plan = planner.plan(&game(), &timer(1000));
+            assert_eq!(plan.len(), 2, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn ties_go_to_wait() {
+        assert_eq!(first_best(&[0u32; 5]), 0);
+        assert_eq!(first_best(&[1, 3, 2, 3, 0]), 1);
+    }
+
+    #[test]
+    fn evaluation_follows_the_weights() {
+        let mut state = game();
+        let base = evaluate(&state);
+        state.params.plan_rum = 2;
+        assert_eq!(evaluate(&state), base + 2f64*(MAX_SHIP_RUM as f64));
+    }
+}
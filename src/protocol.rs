@@ -0,0 +1,214 @@
+use std::fmt;
+use std::io::BufRead;
+use std::time::Instant;
+
+pub enum EntityRecord {
+    Ship {
+        entity_id: i32,
+        x: i32,
+        y: i32,
+        rotation: i32,
+        speed: i32,
+        rum: i32,
+        mine: bool,
+    },
+    Barrel {
+        entity_id: i32,
+        x: i32,
+        y: i32,
+        quantity: i32,
+    },
+    Mine {
+        entity_id: i32,
+        x: i32,
+        y: i32,
+    },
+    Cannonball {
+        entity_id: i32,
+        x: i32,
+        y: i32,
+        owner_id: i32,
+        impact_time: i32,
+    },
+}
+
+pub struct TurnInput {
+    // When the first line of the turn arrived, the turn timer starts there.
+    pub received: Instant,
+    pub ship_count: i32,
+    pub entities: Vec<EntityRecord>,
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, {}: {}", self.line, self.field, self.message)
+    }
+}
+
+fn read_line<R: BufRead>(input: &mut R, line: &mut usize, field: &'static str) -> Result<String, ParseError> {
+    let mut input_line = String::new();
+    *line += 1;
+    match input.read_line(&mut input_line) {
+        Ok(0) => Err(ParseError {line: *line, field: field, message: "unexpected end of input".to_string()}),
+        Ok(_) => Ok(input_line),
+        Err(e) => Err(ParseError {line: *line, field: field, message: e.to_string()}),
+    }
+}
+
+fn parse_field(inputs: &[&str], index: usize, line: usize, field: &'static str) -> Result<i32, ParseError> {
+    let value = inputs.get(index).ok_or_else(|| ParseError {
+        line: line,
+        field: field,
+        message: "missing".to_string(),
+    })?;
+    value.trim().parse::<i32>().map_err(|_| ParseError {
+        line: line,
+        field: field,
+        message: format!("'{}' is not a number", value),
+    })
+}
+
+fn parse_entity(input_line: &str, line: usize) -> Result<EntityRecord, ParseError> {
+    let inputs = input_line.split_whitespace().collect::<Vec<_>>();
+    let entity_id = parse_field(&inputs, 0, line, "entity id")?;
+    let x = parse_field(&inputs, 2, line, "x")?;
+    let y = parse_field(&inputs, 3, line, "y")?;
+    match inputs.get(1) {
+        Some(&"SHIP") => Ok(EntityRecord::Ship {
+            entity_id: entity_id,
+            x: x,
+            y: y,
+            rotation: parse_field(&inputs, 4, line, "rotation")?,
+            speed: parse_field(&inputs, 5, line, "speed")?,
+            rum: parse_field(&inputs, 6, line, "rum")?,
+            mine: parse_field(&inputs, 7, line, "owner")? == 1,
+        }),
+        Some(&"BARREL") => Ok(EntityRecord::Barrel {
+            entity_id: entity_id,
+            x: x,
+            y: y,
+            quantity: parse_field(&inputs, 4, line, "rum")?,
+        }),
+        Some(&"MINE") => Ok(EntityRecord::Mine {
+            entity_id: entity_id,
+            x: x,
+            y: y,
+        }),
+        Some(&"CANNONBALL") => Ok(EntityRecord::Cannonball {
+            entity_id: entity_id,
+            x: x,
+            y: y,
+            owner_id: parse_field(&inputs, 4, line, "owner id")?,
+            impact_time: parse_field(&inputs, 5, line, "impact time")?,
+        }),
+        Some(entity_type) => Err(ParseError {
+            line: line,
+            field: "entity type",
+            message: format!("unknown type '{}'", entity_type),
+        }),
+        None => Err(ParseError {line: line, field: "entity type", message: "missing".to_string()}),
+    }
+}
+
+// Reads the input of one turn, `line` counts the lines consumed so far and is advanced.
+pub fn read_turn<R: BufRead>(input: &mut R, line: &mut usize) -> Result<TurnInput, ParseError> {
+    let input_line = read_line(input, line, "ship count")?;
+    let received = Instant::now();
+    let ship_count = parse_field(&[input_line.as_str()], 0, *line, "ship count")?; // the number of remaining ships
+    let input_line = read_line(input, line, "entity count")?;
+    let entity_count = parse_field(&[input_line.as_str()], 0, *line, "entity count")?; // the number of entities (e.g. ships, mines or cannonballs)
+    let mut entities = Vec::new();
+    for _ in 0..entity_count {
+        let input_line = read_line(input, line, "entity")?;
+        entities.push(parse_entity(&input_line, *line)?);
+    }
+    Ok(TurnInput {
+        received: received,
+        ship_count: ship_count,
+        entities: entities,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use game::Game;
+    use super::*;
+
+    fn error(text: &str) -> ParseError {
+        let mut line = 0;
+        match read_turn(&mut text.as_bytes(), &mut line) {
+            Ok(_) => panic!("'{}' parsed", text),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn reads_every_entity_type() {
+        let text = "1\n4\n0 SHIP 5 7 1 2 93 1\n1 BARREL 3 4 15 0 0 0\n2 MINE 8 9 0 0 0 0\n3 CANNONBALL 10 11 0 2 0 0\n";
+        let mut line = 0;
+        let turn = read_turn(&mut text.as_bytes(), &mut line).unwrap();
+        assert_eq!((turn.ship_count, turn.entities.len(), line), (1, 4, 6));
+        match turn.entities[0] {
+            EntityRecord::Ship {entity_id, x, y, rotation, speed, rum, mine} =>
+                assert_eq!((entity_id, x, y, rotation, speed, rum, mine), (0, 5, 7, 1, 2, 93, true)),
+            _ => panic!("not a ship"),
+        }
+        match turn.entities[3] {
+            EntityRecord::Cannonball {owner_id, impact_time, ..} => assert_eq!((owner_id, impact_time), (0, 2)),
+            _ => panic!("not a cannonball"),
+        }
+    }
+
+    #[test]
+    fn truncated_line_names_the_missing_field() {
+        let e = error("1\n2\n0 SHIP 5 7 1 2 93 1\n1 SHIP 5 9 1 2 93\n");
+        assert_eq!(e.to_string(), "line 4, owner: missing");
+    }
+
+    #[test]
+    fn non_numeric_field_is_reported() {
+        let e = error("1\n1\n0 SHIP 5 seven 1 2 93 1\n");
+        assert_eq!(e.to_string(), "line 3, y: 'seven' is not a number");
+        let e = error("one\n");
+        assert_eq!((e.line, e.field), (1, "ship count"));
+    }
+
+    #[test]
+    fn unknown_entity_type_is_reported() {
+        let e = error("1\n1\n0 KRAKEN 5 7 0 0 0 0\n");
+        assert_eq!(e.to_string(), "line 3, entity type: unknown type 'KRAKEN'");
+    }
+
+    #[test]
+    fn early_end_of_input_is_reported() {
+        let e = error("1\n3\n0 SHIP 5 7 1 2 93 1\n");
+        assert_eq!(e.to_string(), "line 4, entity: unexpected end of input");
+        let e = error("");
+        assert_eq!(e.to_string(), "line 1, ship count: unexpected end of input");
+    }
+
+    #[test]
+    fn line_numbers_carry_over_between_turns() {
+        let text = "1\n1\n0 SHIP 5 7 1 2 93 1\n1\n1\n0 SHIP 5 7 1 2 x 1\n";
+        let mut input = text.as_bytes();
+        let mut line = 0;
+        read_turn(&mut input, &mut line).unwrap();
+        assert_eq!(line, 3);
+        let e = read_turn(&mut input, &mut line).err().unwrap();
+        assert_eq!(e.to_string(), "line 6, rum: 'x' is not a number");
+
+        let mut game = Game::default();
+        let mut input = text.as_bytes();
+        game.init(&mut input).unwrap();
+        assert_eq!(game.lines_read, 3);
+        let e = game.play(&mut input).err().unwrap();
+        assert_eq!((e.line, e.field), (6, "rum"));
+    }
+}
@@ -0,0 +1,714 @@
+use std::cmp;
+use std::collections::HashMap;
+
+use action::Action;
+use entities::{Barrel, Cannoball, Mine, Ship};
+use game::Game;
+use hex::Point;
+use random::Random;
+
+pub const MAX_TURNS: i32 = 200;
+pub const MAX_SHIP_SPEED: i32 = 2;
+pub const MAX_SHIP_RUM: i32 = 100;
+pub const COOLDOWN_CANNON: i32 = 2;
+pub const COOLDOWN_MINE: i32 = 5;
+pub const FIRE_DISTANCE_MAX: i32 = 10;
+pub const LOW_DAMAGE: i32 = 25;
+pub const HIGH_DAMAGE: i32 = 50;
+pub const MINE_DAMAGE: i32 = 25;
+pub const NEAR_MINE_DAMAGE: i32 = 10;
+pub const REWARD_RUM_BARREL_VALUE: i32 = 30;
+pub const MINE_VISIBILITY_RANGE: i32 = 5;
+const MAP_WIDTH: i32 = 23;
+const MAP_HEIGHT: i32 = 21;
+const MIN_RUM_BARREL: i32 = 10;
+const MAX_RUM_BARREL: i32 = 20;
+
+// A ship's position and its order for the turn: (point, rotation, speed, action).
+pub type Order = (Point, i32, i32, Action);
+
+struct ShipState {
+    entity_id: i32,
+    mine: bool,
+    point: Point,
+    rotation: i32,
+    new_rotation: i32,
+    speed: i32,
+    rum: i32,
+    initial_rum: i32,
+    cd: i32,
+    mine_cd: i32,
+    new_point: Point,
+    new_bow: Point,
+    new_stern: Point,
+    // Ran into another ship this turn and stayed put.
+    collided: bool,
+}
+
+impl ShipState {
+    fn new(ship: &Ship, mine: bool) -> ShipState {
+        ShipState {
+            entity_id: ship.entity_id,
+            mine: mine,
+            point: ship.point,
+            rotation: ship.rotation,
+            new_rotation: ship.rotation,
+            speed: ship.speed,
+            rum: ship.rum,
+            initial_rum: ship.rum,
+            cd: ship.cd,
+            mine_cd: ship.mine_cd,
+            new_point: ship.point,
+            new_bow: ship.point,
+            new_stern: ship.point,
+            collided: false,
+        }
+    }
+
+    fn steer(&mut self, action: Action) {
+        match action {
+            Action::FASTER => self.speed = cmp::min(self.speed + 1, MAX_SHIP_SPEED),
+            Action::SLOWER => self.speed = cmp::max(self.speed - 1, 0),
+            Action::PORT => {
+                self.new_rotation = (self.rotation + 1)%6;
+            },
+            Action::STARBOARD => {
+                self.new_rotation = (self.rotation + 5)%6;
+            },
+            _ => {},
+        }
+    }
+
+    fn bow(&self) -> Point {
+        self.point.neighbour(self.rotation)
+    }
+
+    fn stern(&self) -> Point {
+        self.point.neighbour((self.rotation + 3)%6)
+    }
+
+    fn at(&self, point: &Point) -> bool {
+        (self.point == *point) || (self.bow() == *point) || (self.stern() == *point)
+    }
+
+    fn damage(&mut self, amount: i32) {
+        self.rum = cmp::max(0, self.rum - amount);
+    }
+
+    fn heal(&mut self, amount: i32) {
+        self.rum = cmp::min(MAX_SHIP_RUM, self.rum + amount);
+    }
+
+    // Ships keep their heading while moving, PORT and STARBOARD only apply in `rotate_ships`.
+    fn reset_new_position(&mut self) {
+        self.new_point = self.point;
+        self.new_bow = self.bow();
+        self.new_stern = self.stern();
+    }
+
+    fn reset_new_rotation(&mut self) {
+        self.new_point = self.point;
+        self.new_bow = self.point.neighbour(self.new_rotation);
+        self.new_stern = self.point.neighbour((self.new_rotation + 3)%6);
+    }
+
+    fn new_bow_intersects(&self, other: &ShipState) -> bool {
+        (self.new_bow == other.new_bow) || (self.new_bow == other.new_point) || (self.new_bow == other.new_stern)
+    }
+
+    fn new_positions_intersect(&self, other: &ShipState) -> bool {
+        let hull = [self.new_bow, self.new_point, self.new_stern];
+        hull.iter().any(|p| (*p == other.new_bow) || (*p == other.new_point) || (*p == other.new_stern))
+    }
+}
+
+fn explode_mine(ships: &mut [ShipState], point: &Point, force: bool) -> bool {
+    let mut victim = None;
+    for (i, ship) in ships.iter_mut().enumerate() {
+        if ship.at(point) {
+            ship.damage(MINE_DAMAGE);
+            victim = Some(i);
+        }
+    }
+    if !force && victim.is_none() {
+        return false;
+    }
+    for (i, ship) in ships.iter_mut().enumerate() {
+        if victim == Some(i) {
+            continue;
+        }
+        if (ship.point.distance(point) <= 1) || (ship.bow().distance(point) <= 1) || (ship.stern().distance(point) <= 1) {
+            ship.damage(NEAR_MINE_DAMAGE);
+        }
+    }
+    true
+}
+
+fn check_collisions(ships: &mut [ShipState], barrels: &mut Vec<Barrel>, mines: &mut Vec<Mine>) {
+    for ship in ships.iter_mut() {
+        barrels.retain(|barrel| {
+            if ship.at(&barrel.point) {
+                ship.heal(barrel.quantity);
+                false
+            } else {
+                true
+            }
+        });
+    }
+    mines.retain(|mine| !explode_mine(ships, &mine.point, false));
+}
+
+fn move_ships(ships: &mut [ShipState], barrels: &mut Vec<Barrel>, mines: &mut Vec<Mine>) {
+    for step in 1..(MAX_SHIP_SPEED + 1) {
+        for ship in ships.iter_mut() {
+            ship.reset_new_position();
+            if step > ship.speed {
+                continue;
+            }
+            let point = ship.point.neighbour(ship.rotation);
+            if point.is_inside() {
+                ship.new_point = point;
+                ship.new_bow = point.neighbour(ship.rotation);
+                ship.new_stern = point.neighbour((ship.rotation + 3)%6);
+            } else {
+                ship.speed = 0;
+            }
+        }
+        loop {
+            let collided: Vec<usize> = (0..ships.len())
+                .filter(|&i| (0..ships.len()).any(|j| (i != j) && ships[i].new_bow_intersects(&ships[j])))
+                .collect();
+            let mut changed = false;
+            for i in collided {
+                let moved = ships[i].new_point != ships[i].point;
+                changed = changed || moved;
+                ships[i].collided = ships[i].collided || moved;
+                ships[i].reset_new_position();
+                ships[i].speed = 0;
+            }
+            if !changed {
+                break;
+            }
+        }
+        for ship in ships.iter_mut() {
+            ship.point = ship.new_point;
+        }
+        check_collisions(ships, barrels, mines);
+    }
+}
+
+fn rotate_ships(ships: &mut [ShipState], barrels: &mut Vec<Barrel>, mines: &mut Vec<Mine>) {
+    for ship in ships.iter_mut() {
+        ship.reset_new_rotation();
+    }
+    loop {
+        let collided: Vec<usize> = (0..ships.len())
+            .filter(|&i| (0..ships.len()).any(|j| (i != j) && ships[i].new_positions_intersect(&ships[j])))
+            .collect();
+        let mut changed = false;
+        for i in collided {
+            let turned = ships[i].new_rotation != ships[i].rotation;
+            changed = changed || turned;
+            ships[i].collided = ships[i].collided || turned;
+            ships[i].new_rotation = ships[i].rotation;
+            ships[i].reset_new_rotation();
+            ships[i].speed = 0;
+        }
+        if !changed {
+            break;
+        }
+    }
+    for ship in ships.iter_mut() {
+        ship.rotation = ship.new_rotation;
+    }
+    check_collisions(ships, barrels, mines);
+}
+
+// Turns a cannonball fired at a target `distance` tiles from the bow flies, this is what the
+// CANNONBALL entity reports on the next turn.
+pub fn travel_time(distance: i32) -> i32 {
+    1 + (distance + 1)/3
+}
+
+// Where a ship ends up after one `action` if nothing else is on the board: speed change,
+// up to `speed` steps forward stopping at the map border, then rotation.
+pub fn move_alone(point: Point, rotation: i32, speed: i32, action: Action) -> (Point, i32, i32) {
+    let mut point = point;
+    let mut rotation = rotation;
+    let mut speed = match action {
+        Action::FASTER => cmp::min(speed + 1, MAX_SHIP_SPEED),
+        Action::SLOWER => cmp::max(speed - 1, 0),
+        _ => speed,
+    };
+    for _ in 0..speed {
+        let next = point.neighbour(rotation);
+        if !next.is_inside() {
+            speed = 0;
+            break;
+        }
+        point = next;
+    }
+    match action {
+        Action::PORT => rotation = (rotation + 1)%6,
+        Action::STARBOARD => rotation = (rotation + 5)%6,
+        _ => {},
+    }
+    (point, rotation, speed)
+}
+
+// Where every ship ends up after its order when only the ships matter: the movement and rotation
+// phases of `next_turn` with their collisions, no barrels, mines or cannonballs. Returns the
+// (point, rotation, speed) of each ship and whether it ran into another one.
+pub fn move_fleet(orders: &[Order]) -> Vec<(Point, i32, i32, bool)> {
+    let mut ships: Vec<ShipState> = orders.iter().map(|&(point, rotation, speed, action)| {
+        let mut ship = ShipState::new(&Ship::new(-1, point.x, point.y, rotation, speed, MAX_SHIP_RUM), true);
+        ship.steer(action);
+        ship
+    }).collect();
+    let (mut barrels, mut mines) = (Vec::new(), Vec::new());
+    move_ships(&mut ships, &mut barrels, &mut mines);
+    rotate_ships(&mut ships, &mut barrels, &mut mines);
+    ships.iter().map(|ship| (ship.point, ship.rotation, ship.speed, ship.collided)).collect()
+}
+
+fn next_entity_id(game: &Game) -> i32 {
+    let ids = game.my_ships.keys()
+        .chain(game.enemy_ships.keys())
+        .chain(game.barrels.keys())
+        .chain(game.mines.keys())
+        .chain(game.cannonballs.keys());
+    ids.fold(-1, |m, id| cmp::max(m, *id)) + 1
+}
+
+// Applies one turn of the contest rules to `game`. `actions` holds the order of every
+// ship by entity id, ships without an entry WAIT. Entities that are gone keep their
+// stale `tick_accessed`, the same way `Game::play` forgets them.
+pub fn next_turn(game: &Game, actions: &HashMap<i32, Action>) -> Game {
+    let tick = game.current_tick;
+    let mut next_id = next_entity_id(game);
+
+    let mut ships: Vec<ShipState> = game.my_ships.values()
+        .filter(|ship| ship.is_alive(tick))
+        .map(|ship| ShipState::new(ship, true))
+        .chain(game.enemy_ships.values()
+               .filter(|ship| ship.is_alive(tick))
+               .map(|ship| ShipState::new(ship, false)))
+        .collect();
+    ships.sort_by_key(|ship| (!ship.mine, ship.entity_id));
+    let mut barrels: Vec<Barrel> = game.barrels.values().filter(|b| b.is_alive(tick)).cloned().collect();
+    barrels.sort_by_key(|barrel| barrel.entity_id);
+    let mut mines: Vec<Mine> = game.mines.values().filter(|m| m.is_alive(tick)).cloned().collect();
+    mines.sort_by_key(|mine| mine.entity_id);
+    let mut cannonballs: Vec<Cannoball> = game.cannonballs.values().filter(|c| c.is_alive(tick)).cloned().collect();
+    cannonballs.sort_by_key(|cannonball| cannonball.entity_id);
+
+    let (landing, cannonballs): (Vec<Cannoball>, Vec<Cannoball>) =
+        cannonballs.into_iter().partition(|cannonball| cannonball.impact_time <= 0);
+    let mut explosions: Vec<Point> = landing.iter().map(|cannonball| cannonball.target).collect();
+    let mut new_cannonballs = Vec::new();
+
+    for ship in ships.iter_mut() {
+        ship.damage(1);
+        ship.initial_rum = ship.rum;
+    }
+
+    for i in 0..ships.len() {
+        let action = actions.get(&ships[i].entity_id).cloned().unwrap_or(Action::WAIT);
+        match action {
+            Action::MINE => {
+                let target = ships[i].stern().neighbour((ships[i].rotation + 3)%6);
+                let free = (ships[i].mine_cd == 0) && target.is_inside() &&
+                    !barrels.iter().any(|barrel| barrel.point == target) &&
+                    !mines.iter().any(|mine| mine.point == target) &&
+                    !ships.iter().enumerate().any(|(j, ship)| (i != j) && ship.at(&target));
+                if free {
+                    ships[i].mine_cd = COOLDOWN_MINE;
+                    let mine = Mine::new(next_id, target.x, target.y);
+                    next_id += 1;
+                    mines.push(mine);
+                }
+            },
+            Action::FIRE(x, y) => {
+                let target = Point::new(x, y);
+                let distance = ships[i].bow().distance(&target);
+                if target.is_inside() && (distance <= FIRE_DISTANCE_MAX) && (ships[i].cd == 0) {
+                    let travel_time = travel_time(distance);
+                    ships[i].cd = COOLDOWN_CANNON;
+                    new_cannonballs.push(Cannoball::new(next_id, ships[i].entity_id, travel_time, x, y));
+                    next_id += 1;
+                }
+            },
+            _ => ships[i].steer(action),
+        }
+    }
+
+    move_ships(&mut ships, &mut barrels, &mut mines);
+    rotate_ships(&mut ships, &mut barrels, &mut mines);
+
+    explosions.retain(|point| {
+        for ship in ships.iter_mut() {
+            if (ship.bow() == *point) || (ship.stern() == *point) {
+                ship.damage(LOW_DAMAGE);
+                return false;
+            }
+            if ship.point == *point {
+                ship.damage(HIGH_DAMAGE);
+                return false;
+            }
+        }
+        true
+    });
+    explosions.retain(|point| {
+        match mines.iter().position(|mine| mine.point == *point) {
+            Some(ind) => {
+                mines.remove(ind);
+                explode_mine(&mut ships, point, true);
+                false
+            },
+            None => true,
+        }
+    });
+    explosions.retain(|point| {
+        match barrels.iter().position(|barrel| barrel.point == *point) {
+            Some(ind) => {
+                barrels.remove(ind);
+                false
+            },
+            None => true,
+        }
+    });
+    for ship in ships.iter() {
+        if ship.rum > 0 {
+            continue;
+        }
+        let reward = cmp::min(REWARD_RUM_BARREL_VALUE, ship.initial_rum);
+        if reward > 0 {
+            barrels.push(Barrel::new(next_id, ship.point.x, ship.point.y, reward));
+            next_id += 1;
+        }
+    }
+
+    let mut next = game.clone();
+    next.current_tick = tick + 1;
+    for ship in ships.iter() {
+        if ship.rum <= 0 {
+            continue;
+        }
+        let n_ship = if ship.mine {
+            next.my_ships.get_mut(&ship.entity_id).unwrap()
+        } else {
+            next.enemy_ships.get_mut(&ship.entity_id).unwrap()
+        };
+        n_ship.tick_accessed = next.current_tick;
+        n_ship.point = ship.point;
+        n_ship.rotation = ship.rotation;
+        n_ship.speed = ship.speed;
+        n_ship.rum = ship.rum;
+        n_ship.cd = cmp::max(0, ship.cd - 1);
+        n_ship.mine_cd = cmp::max(0, ship.mine_cd - 1);
+    }
+    for barrel in barrels {
+        next.barrels.entry(barrel.entity_id).or_insert(barrel).keep_alive(next.current_tick);
+    }
+    for mine in mines {
+        next.mines.entry(mine.entity_id).or_insert(mine).keep_alive(next.current_tick);
+    }
+    for cannonball in cannonballs.into_iter().chain(new_cannonballs) {
+        next.cannonballs.entry(cannonball.entity_id).or_insert(cannonball).keep_alive(next.current_tick);
+    }
+    next.calc_under_fire();
+    next
+}
+
+pub fn total_rum(game: &Game) -> (i32, i32) {
+    let tick = game.current_tick;
+    let mine = game.my_ships.values().filter(|ship| ship.is_alive(tick)).map(|ship| ship.rum).sum();
+    let enemy = game.enemy_ships.values().filter(|ship| ship.is_alive(tick)).map(|ship| ship.rum).sum();
+    (mine, enemy)
+}
+
+pub fn is_over(game: &Game) -> bool {
+    let tick = game.current_tick;
+    if tick >= MAX_TURNS {
+        return true;
+    }
+    !game.my_ships.values().any(|ship| ship.is_alive(tick)) ||
+        !game.enemy_ships.values().any(|ship| ship.is_alive(tick))
+}
+
+// Builds a starting position the way the contest does: 1-3 ships per player, 5-10 mines
+// and 10-26 barrels, mirrored between the top (player 0) and bottom (player 1) halves.
+pub fn generate(seed: u64) -> Game {
+    let mut random = Random::new(seed);
+    let mut game = Game::default();
+    let mut next_id = 0;
+    let ships_per_player = 1 + random.next_int(3);
+    for j in 0..ships_per_player {
+        let x_min = 1 + j*MAP_WIDTH/ships_per_player;
+        let x_max = (j + 1)*MAP_WIDTH/ships_per_player - 2;
+        let x = x_min + random.next_int(1 + x_max - x_min);
+        let y = 1 + random.next_int(MAP_HEIGHT/2 - 2);
+        let rotation = random.next_int(6);
+        game.my_ships.insert(next_id, Ship::new(next_id, x, y, rotation, 0, MAX_SHIP_RUM));
+        game.my_ships_ids.push(next_id);
+        game.enemy_ships.insert(next_id + 1, Ship::new(next_id + 1, x, MAP_HEIGHT - 1 - y, (6 - rotation)%6, 0, MAX_SHIP_RUM));
+        next_id += 2;
+    }
+
+    let mut occupied: Vec<Point> = Vec::new();
+    for ship in game.my_ships.values().chain(game.enemy_ships.values()) {
+        occupied.push(ship.point);
+        occupied.push(ship.point.neighbour(ship.rotation));
+        occupied.push(ship.point.neighbour((ship.rotation + 3)%6));
+    }
+    let mine_count = 5 + random.next_int(6);
+    let mut mines: Vec<Point> = Vec::new();
+    while (mines.len() as i32) < mine_count {
+        let point = Point::new(1 + random.next_int(MAP_WIDTH - 2), 1 + random.next_int(MAP_HEIGHT/2));
+        if occupied.contains(&point) || mines.contains(&point) {
+            continue;
+        }
+        mines.push(point);
+        let mirror = Point::new(point.x, MAP_HEIGHT - 1 - point.y);
+        if mirror != point {
+            mines.push(mirror);
+        }
+    }
+    for point in mines.iter() {
+        game.mines.insert(next_id, Mine::new(next_id, point.x, point.y));
+        next_id += 1;
+    }
+
+    let barrel_count = 10 + random.next_int(17);
+    let mut barrels: Vec<(Point, i32)> = Vec::new();
+    while (barrels.len() as i32) < barrel_count {
+        let point = Point::new(1 + random.next_int(MAP_WIDTH - 2), 1 + random.next_int(MAP_HEIGHT/2));
+        let quantity = MIN_RUM_BARREL + random.next_int(1 + MAX_RUM_BARREL - MIN_RUM_BARREL);
+        if occupied.contains(&point) || mines.contains(&point) || barrels.iter().any(|&(p, _)| p == point) {
+            continue;
+        }
+        barrels.push((point, quantity));
+        let mirror = Point::new(point.x, MAP_HEIGHT - 1 - point.y);
+        if mirror != point {
+            barrels.push((mirror, quantity));
+        }
+    }
+    for &(point, quantity) in barrels.iter() {
+        game.barrels.insert(next_id, Barrel::new(next_id, point.x, point.y, quantity));
+        next_id += 1;
+    }
+    game.calc_under_fire();
+    game
+}
+
+// Entity ids of the alive ships of `player` (0 owns `my_ships`), in the order they are
+// sent to the bot and so in the order it answers.
+pub fn ship_ids(game: &Game, player: i32) -> Vec<i32> {
+    let ships = if player == 0 {&game.my_ships} else {&game.enemy_ships};
+    let mut ids: Vec<i32> = ships.values()
+        .filter(|ship| ship.is_alive(game.current_tick))
+        .map(|ship| ship.entity_id)
+        .collect();
+    ids.sort();
+    ids
+}
+
+// The stdin block `player` receives this turn, mines further than MINE_VISIBILITY_RANGE
+// from all of its ships are hidden like on CodinGame.
+pub fn player_input(game: &Game, player: i32) -> String {
+    let tick = game.current_tick;
+    let (own, other) = if player == 0 {
+        (&game.my_ships, &game.enemy_ships)
+    } else {
+        (&game.enemy_ships, &game.my_ships)
+    };
+    let mut entities: Vec<(i32, String)> = Vec::new();
+    for (ships, owner) in [(own, 1), (other, 0)].iter() {
+        for ship in ships.values().filter(|ship| ship.is_alive(tick)) {
+            entities.push((ship.entity_id, format!("{} SHIP {} {} {} {} {} {}", ship.entity_id,
+                           ship.point.x, ship.point.y, ship.rotation, ship.speed, ship.rum, owner)));
+        }
+    }
+    for barrel in game.barrels.values().filter(|barrel| barrel.is_alive(tick)) {
+        entities.push((barrel.entity_id, format!("{} BARREL {} {} {} 0 0 0", barrel.entity_id,
+                       barrel.point.x, barrel.point.y, barrel.quantity)));
+    }
+    for cannonball in game.cannonballs.values().filter(|cannonball| cannonball.is_alive(tick)) {
+        entities.push((cannonball.entity_id, format!("{} CANNONBALL {} {} {} {} 0 0", cannonball.entity_id,
+                       cannonball.target.x, cannonball.target.y, cannonball.owner_id, cannonball.impact_time + 1)));
+    }
+    for mine in game.mines.values().filter(|mine| mine.is_alive(tick)) {
+        let visible = own.values()
+            .filter(|ship| ship.is_alive(tick))
+            .any(|ship| ship.point.distance(&mine.point) <= MINE_VISIBILITY_RANGE);
+        if visible {
+            entities.push((mine.entity_id, format!("{} MINE {} {} 0 0 0 0", mine.entity_id, mine.point.x, mine.point.y)));
+        }
+    }
+    entities.sort_by_key(|&(id, _)| id);
+    let mut input = format!("{}\n{}\n", ship_ids(game, player).len(), entities.len());
+    for (_, line) in entities {
+        input.push_str(&line);
+        input.push('\n');
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use action::Action;
+    use entities::{Barrel, Cannoball, Mine, Ship};
+    use game::Game;
+    use hex::Point;
+    use super::*;
+
+    fn ship(entity_id: i32, x: i32, y: i32, rotation: i32, speed: i32) -> Ship {
+        Ship::new(entity_id, x, y, rotation, speed, MAX_SHIP_RUM)
+    }
+
+    // Ship 1 is ours, ship 2 the enemy's.
+    fn duel(mine: Ship, enemy: Ship) -> Game {
+        let mut game = Game::default();
+        game.my_ships_ids.push(mine.entity_id);
+        game.my_ships.insert(mine.entity_id, mine);
+        game.enemy_ships.insert(enemy.entity_id, enemy);
+        game
+    }
+
+    fn turn(game: &Game, orders: &[(i32, Action)]) -> Game {
+        let actions: HashMap<i32, Action> = orders.iter().cloned().collect();
+        next_turn(game, &actions)
+    }
+
+    #[test]
+    fn ships_meeting_head_on_stop() {
+        let game = duel(ship(1, 5, 5, 0, 1), ship(2, 8, 5, 3, 1));
+        let next = turn(&game, &[]);
+        assert_eq!((next.my_ships[&1].point, next.my_ships[&1].speed), (Point::new(5, 5), 0));
+        assert_eq!((next.enemy_ships[&2].point, next.enemy_ships[&2].speed), (Point::new(8, 5), 0));
+    }
+
+    #[test]
+    fn turning_ship_keeps_its_heading_while_others_move() {
+        // The enemy bow reaches (6, 5), the bow of our ship before it turns.
+        let game = duel(ship(1, 5, 5, 0, 0), ship(2, 8, 5, 3, 1));
+        let next = turn(&game, &[(1, Action::PORT)]);
+        assert_eq!((next.enemy_ships[&2].point, next.enemy_ships[&2].speed), (Point::new(8, 5), 0));
+        assert_eq!(next.my_ships[&1].rotation, 1);
+    }
+
+    #[test]
+    fn turning_into_a_hull_is_cancelled() {
+        // Turning to port puts our bow on (6, 4), the bow of the enemy.
+        let game = duel(ship(1, 5, 5, 0, 0), ship(2, 7, 4, 3, 0));
+        let next = turn(&game, &[(1, Action::PORT)]);
+        assert_eq!((next.my_ships[&1].rotation, next.my_ships[&1].speed), (0, 0));
+    }
+
+    #[test]
+    fn fleet_bow_into_the_middle_of_a_hull() {
+        let moved = move_fleet(&[(Point::new(5, 5), 0, 1, Action::WAIT), (Point::new(7, 5), 2, 0, Action::WAIT)]);
+        assert_eq!(moved, vec![(Point::new(5, 5), 0, 0, true), (Point::new(7, 5), 2, 0, false)]);
+    }
+
+    #[test]
+    fn fleet_bow_into_a_stern() {
+        let moved = move_fleet(&[(Point::new(5, 5), 0, 1, Action::WAIT), (Point::new(8, 5), 0, 0, Action::WAIT)]);
+        assert_eq!(moved, vec![(Point::new(5, 5), 0, 0, true), (Point::new(8, 5), 0, 0, false)]);
+    }
+
+    #[test]
+    fn fleet_turning_into_a_hull() {
+        let moved = move_fleet(&[(Point::new(5, 5), 0, 0, Action::PORT), (Point::new(7, 4), 3, 0, Action::WAIT)]);
+        assert_eq!(moved, vec![(Point::new(5, 5), 0, 0, true), (Point::new(7, 4), 3, 0, false)]);
+    }
+
+    #[test]
+    fn fleet_meeting_head_on_both_stop() {
+        let moved = move_fleet(&[(Point::new(5, 5), 0, 1, Action::WAIT), (Point::new(8, 5), 3, 1, Action::WAIT)]);
+        assert_eq!(moved, vec![(Point::new(5, 5), 0, 0, true), (Point::new(8, 5), 3, 0, true)]);
+    }
+
+    #[test]
+    fn fleet_collision_stops_after_the_last_free_step() {
+        // The first step is clear, the second one puts the bow on the other stern.
+        let moved = move_fleet(&[(Point::new(5, 5), 0, 2, Action::WAIT), (Point::new(9, 5), 0, 0, Action::WAIT)]);
+        assert_eq!(moved[0], (Point::new(6, 5), 0, 0, true));
+    }
+
+    #[test]
+    fn fleet_without_contact_keeps_its_speed() {
+        let moved = move_fleet(&[(Point::new(5, 5), 0, 1, Action::PORT), (Point::new(5, 12), 3, 2, Action::WAIT)]);
+        assert_eq!(moved, vec![(Point::new(6, 5), 1, 1, false), (Point::new(3, 12), 3, 2, false)]);
+    }
+
+    #[test]
+    fn cannonball_lands_after_its_flight_time() {
+        let mut game = duel(ship(1, 5, 10, 0, 0), ship(2, 12, 10, 0, 0));
+        game.my_ships.get_mut(&1).unwrap().rum = 50;
+        let flight = travel_time(Point::new(6, 10).distance(&Point::new(12, 10)));
+        let mut next = turn(&game, &[(1, Action::FIRE(12, 10))]);
+        assert_eq!(next.cannonballs.values().next().unwrap().impact_time + 1, flight);
+        for _ in 1..flight {
+            next = turn(&next, &[]);
+            assert_eq!(next.cannonballs.values().filter(|c| c.is_alive(next.current_tick)).count(), 1);
+        }
+        let rum = next.enemy_ships[&2].rum;
+        next = turn(&next, &[]);
+        assert_eq!(next.enemy_ships[&2].rum, rum - 1 - HIGH_DAMAGE);
+    }
+
+    #[test]
+    fn cannonball_damage_depends_on_the_tile_hit() {
+        for &(x, damage) in [(10, HIGH_DAMAGE), (11, LOW_DAMAGE), (9, LOW_DAMAGE)].iter() {
+            let mut game = duel(ship(1, 10, 10, 0, 0), ship(2, 3, 3, 0, 0));
+            game.cannonballs.insert(5, Cannoball::new(5, 2, 0, x, 10));
+            let next = turn(&game, &[]);
+            assert_eq!(next.my_ships[&1].rum, MAX_SHIP_RUM - 1 - damage);
+        }
+    }
+
+    #[test]
+    fn mine_damages_the_ship_on_it() {
+        let mut game = duel(ship(1, 5, 5, 0, 1), ship(2, 3, 15, 0, 0));
+        game.mines.insert(5, Mine::new(5, 7, 5));
+        let next = turn(&game, &[]);
+        assert_eq!(next.my_ships[&1].rum, MAX_SHIP_RUM - 1 - MINE_DAMAGE);
+        assert!(!next.mines[&5].is_alive(next.current_tick));
+    }
+
+    #[test]
+    fn exploding_mine_damages_ships_nearby() {
+        // Our stern (11, 10) is next to the mine a cannonball sets off.
+        let mut game = duel(ship(1, 12, 10, 0, 0), ship(2, 3, 15, 0, 0));
+        game.mines.insert(5, Mine::new(5, 10, 10));
+        game.cannonballs.insert(6, Cannoball::new(6, 2, 0, 10, 10));
+        let next = turn(&game, &[]);
+        assert_eq!(next.my_ships[&1].rum, MAX_SHIP_RUM - 1 - NEAR_MINE_DAMAGE);
+        assert!(!next.mines[&5].is_alive(next.current_tick));
+    }
+
+    #[test]
+    fn sunk_ship_drops_its_rum() {
+        let mut game = duel(ship(1, 10, 10, 0, 0), ship(2, 3, 15, 0, 0));
+        game.my_ships.get_mut(&1).unwrap().rum = 30;
+        game.cannonballs.insert(5, Cannoball::new(5, 2, 0, 10, 10));
+        let next = turn(&game, &[]);
+        assert!(!next.my_ships[&1].is_alive(next.current_tick));
+        let barrels: Vec<&Barrel> = next.barrels.values().filter(|b| b.is_alive(next.current_tick)).collect();
+        assert_eq!(barrels.len(), 1);
+        assert_eq!((barrels[0].point, barrels[0].quantity), (Point::new(10, 10), 29));
+    }
+
+    #[test]
+    fn barrel_heals_the_ship_picking_it() {
+        let mut game = duel(ship(1, 5, 5, 0, 1), ship(2, 3, 15, 0, 0));
+        game.my_ships.get_mut(&1).unwrap().rum = 50;
+        game.barrels.insert(5, Barrel::new(5, 7, 5, 15));
+        let next = turn(&game, &[]);
+        assert_eq!(next.my_ships[&1].rum, 50 - 1 + 15);
+        assert!(!next.barrels[&5].is_alive(next.current_tick));
+    }
+}
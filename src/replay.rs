@@ -0,0 +1,206 @@
+use std::io::{self, BufRead, Read, Write};
+
+use action::Action;
+use game::Game;
+
+// Replay files are plain text, one block per turn:
+//
+//     # turn 3
+//     > 2
+//     > 14
+//     > 0 SHIP 5 7 1 2 93 1
+//     ...
+//     < FASTER
+//     < FIRE 12 9 YARRR
+//
+// `>` lines are the raw input of the turn and `<` lines the actions the bot answered.
+pub struct Turn {
+    pub input: String,
+    pub actions: Vec<String>,
+}
+
+pub struct Mismatch {
+    pub turn: usize,
+    pub recorded: Vec<String>,
+    pub actual: Vec<String>,
+}
+
+// BufRead wrapper keeping a copy of everything consumed, so the raw text of a turn can be
+// recorded after `Game::play` has parsed it.
+pub struct TeeReader<R> {
+    inner: R,
+    captured: Vec<u8>,
+}
+
+impl<R: BufRead> TeeReader<R> {
+    pub fn new(inner: R) -> TeeReader<R> {
+        TeeReader {
+            inner: inner,
+            captured: Vec::new(),
+        }
+    }
+
+    pub fn take_captured(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.captured).into_owned();
+        self.captured.clear();
+        text
+    }
+}
+
+impl<R: BufRead> Read for TeeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.captured.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<R: BufRead> BufRead for TeeReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Ok(buf) = self.inner.fill_buf() {
+            let n = amt.min(buf.len());
+            self.captured.extend_from_slice(&buf[..n]);
+        }
+        self.inner.consume(amt);
+    }
+}
+
+pub struct Recorder<W: Write> {
+    out: W,
+    turn: usize,
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(out: W) -> Recorder<W> {
+        Recorder {
+            out: out,
+            turn: 0,
+        }
+    }
+
+    pub fn record(&mut self, input: &str, actions: &[Action]) -> io::Result<()> {
+        writeln!(self.out, "# turn {}", self.turn)?;
+        for line in input.lines() {
+            writeln!(self.out, "> {}", line)?;
+        }
+        for action in actions {
+            writeln!(self.out, "< {}", action)?;
+        }
+        self.turn += 1;
+        self.out.flush()
+    }
+}
+
+pub fn read_replay<R: BufRead>(input: R) -> Result<Vec<Turn>, String> {
+    let mut turns: Vec<Turn> = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        if line.starts_with("# turn") {
+            turns.push(Turn {input: String::new(), actions: Vec::new()});
+            continue;
+        }
+        let turn = match turns.last_mut() {
+            Some(turn) => turn,
+            None if line.trim().is_empty() => continue,
+            None => return Err(format!("line {}: expected '# turn'", i + 1)),
+        };
+        if let Some(text) = line.strip_prefix("> ") {
+            turn.input.push_str(text);
+            turn.input.push('\n');
+        } else if let Some(text) = line.strip_prefix("< ") {
+            turn.actions.push(text.to_string());
+        } else if !line.trim().is_empty() {
+            return Err(format!("line {}: unexpected '{}'", i + 1, line));
+        }
+    }
+    Ok(turns)
+}
+
+// Feeds the recorded turns to a freshly configured `game` and returns the turns where it now
+// answers differently. Actions are compared parsed, so FIRE messages do not matter.
+pub fn replay(turns: &[Turn], mut game: Game) -> Result<Vec<Mismatch>, String> {
+    let mut mismatches = Vec::new();
+    for (i, turn) in turns.iter().enumerate() {
+        let mut input = turn.input.as_bytes();
+        let actions = if i == 0 {game.init(&mut input)} else {game.play(&mut input)};
+        let actions = actions.map_err(|e| format!("turn {}: {}", i, e))?;
+        let recorded: Vec<Option<Action>> = turn.actions.iter().map(|line| Action::parse(line)).collect();
+        let same = (recorded.len() == actions.len()) &&
+            recorded.iter().zip(actions.iter()).all(|(r, a)| *r == Some(*a));
+        if !same {
+            mismatches.push(Mismatch {
+                turn: i,
+                recorded: turn.actions.clone(),
+                actual: actions.iter().map(|action| action.to_string()).collect(),
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    const TURNS: [&str; 2] = [
+        "1\n3\n0 SHIP 5 7 0 0 100 1\n1 SHIP 15 12 3 0 100 0\n2 BARREL 9 7 15 0 0 0\n",
+        "1\n3\n0 SHIP 5 7 0 1 99 1\n1 SHIP 15 12 3 0 99 0\n2 BARREL 9 7 15 0 0 0\n",
+    ];
+
+    // Plays `TURNS` through a `TeeReader` the way the binary does, recording every answer.
+    fn record() -> (Vec<u8>, Vec<Vec<Action>>) {
+        ::LOG.store(false, Ordering::Relaxed);
+        let text: String = TURNS.concat();
+        let mut input = TeeReader::new(text.as_bytes());
+        let mut recorder = Recorder::new(Vec::new());
+        let mut game = Game::default();
+        let mut answers = Vec::new();
+        for (i, turn) in TURNS.iter().enumerate() {
+            let actions = if i == 0 {game.init(&mut input)} else {game.play(&mut input)}.unwrap();
+            assert_eq!(input.take_captured(), *turn);
+            recorder.record(turn, &actions).unwrap();
+            answers.push(actions);
+        }
+        (recorder.out, answers)
+    }
+
+    #[test]
+    fn recorded_turns_read_back() {
+        let (out, answers) = record();
+        let turns = read_replay(&out[..]).unwrap();
+        assert_eq!(turns.len(), TURNS.len());
+        for (i, turn) in turns.iter().enumerate() {
+            assert_eq!(turn.input, TURNS[i]);
+            let actions: Vec<String> = answers[i].iter().map(|action| action.to_string()).collect();
+            assert_eq!(turn.actions, actions);
+        }
+    }
+
+    #[test]
+    fn replaying_a_recording_matches() {
+        let (out, _) = record();
+        let turns = read_replay(&out[..]).unwrap();
+        assert!(replay(&turns, Game::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn changed_answer_is_a_mismatch() {
+        let (out, _) = record();
+        let mut turns = read_replay(&out[..]).unwrap();
+        turns[1].actions = vec!["MINE".to_string()];
+        let mismatches = replay(&turns, Game::default()).unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!((mismatches[0].turn, &mismatches[0].recorded), (1, &turns[1].actions));
+    }
+
+    #[test]
+    fn text_before_the_first_turn_is_rejected() {
+        assert_eq!(read_replay(&b"\n> 1\n"[..]).err(), Some("line 2: expected '# turn'".to_string()));
+    }
+}
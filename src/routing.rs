@@ -0,0 +1,110 @@
+use std::collections::VecDeque;
+
+use board::{Bitboard, Board, TILES};
+use entities::{Barrel, Ship};
+use referee::{self, MAX_SHIP_RUM, MAX_SHIP_SPEED};
+use targeting::MOVES;
+
+// Turns of moves searched, enough to cross the map.
+const ROUTE_TURNS: i32 = 15;
+pub const UNREACHABLE: i32 = i32::MAX;
+
+// First turn some part of the hull of `ship` can be on each tile, by breadth-first search over
+// its (position, rotation, speed) states. A state with the hull on one of `mines` ends the
+// route, other ships and cannonballs are ignored. A speed 2 move also sweeps the tiles between
+// the old and the new hull, those are covered by one of the two.
+pub fn arrival_turns(ship: &Ship, mines: &Bitboard) -> Vec<i32> {
+    let mut arrival = vec![UNREACHABLE; TILES];
+    let speeds = (MAX_SHIP_SPEED + 1) as usize;
+    let mut visited = vec![false; TILES*6*speeds];
+    let mut queue = VecDeque::new();
+    if !ship.point.is_inside() {
+        return arrival;
+    }
+    queue.push_back((ship.point, ship.rotation, ship.speed, 0));
+    while let Some((point, rotation, speed, turn)) = queue.pop_front() {
+        let state = (Board::index(&point)*6 + rotation as usize)*speeds + speed as usize;
+        if visited[state] {
+            continue;
+        }
+        visited[state] = true;
+        if (turn > 0) && (Bitboard::hull(&point, rotation) & *mines).count() > 0 {
+            continue;
+        }
+        for tile in [point, point.neighbour(rotation), point.neighbour((rotation + 3)%6)].iter() {
+            if tile.is_inside() && (arrival[Board::index(tile)] > turn) {
+                arrival[Board::index(tile)] = turn;
+            }
+        }
+        if turn == ROUTE_TURNS {
+            continue;
+        }
+        for action in MOVES.iter() {
+            let (n_point, n_rotation, n_speed) = referee::move_alone(point, rotation, speed, *action);
+            queue.push_back((n_point, n_rotation, n_speed, turn + 1));
+        }
+    }
+    arrival
+}
+
+// Rum a barrel gives `ship` per turn spent getting there, the ship losing one rum every turn.
+pub fn barrel_value(ship: &Ship, barrel: &Barrel, turns: i32) -> f64 {
+    let gain = barrel.quantity.min(MAX_SHIP_RUM - ship.rum).max(1);
+    (gain as f64)/((turns + 1) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use entities::Barrel;
+    use hex::Point;
+    use super::*;
+
+    fn arrival(ship: &Ship, mines: &Bitboard, x: i32, y: i32) -> i32 {
+        arrival_turns(ship, mines)[Board::index(&Point::new(x, y))]
+    }
+
+    #[test]
+    fn hull_reaches_tiles_ahead_by_speed() {
+        let ship = Ship::new(0, 5, 10, 0, 1, 50);
+        let none = Bitboard::default();
+        assert_eq!(arrival(&ship, &none, 4, 10), 0);
+        assert_eq!(arrival(&ship, &none, 6, 10), 0);
+        assert_eq!(arrival(&ship, &none, 7, 10), 1);
+        // FASTER then two tiles a turn: the bow is on 8 after one turn, on 12 after three.
+        assert_eq!(arrival(&ship, &none, 8, 10), 1);
+        assert_eq!(arrival(&ship, &none, 12, 10), 3);
+    }
+
+    #[test]
+    fn mine_ahead_forces_a_detour() {
+        let ship = Ship::new(0, 5, 10, 0, 0, 50);
+        let none = Bitboard::default();
+        let mut mines = Bitboard::default();
+        mines.insert(&Point::new(7, 10));
+        assert_eq!(arrival(&ship, &none, 9, 10), 2);
+        assert!(arrival(&ship, &mines, 9, 10) > 2);
+        assert!(arrival(&ship, &mines, 9, 10) < UNREACHABLE);
+        // The mine tile itself is never covered.
+        assert_eq!(arrival(&ship, &mines, 7, 10), UNREACHABLE);
+    }
+
+    #[test]
+    fn search_stops_after_the_route_turns() {
+        let ship = Ship::new(0, 0, 0, 0, 0, 50);
+        let arrival = arrival_turns(&ship, &Bitboard::default());
+        assert!(arrival.iter().all(|&turns| (turns == UNREACHABLE) || (turns <= ROUTE_TURNS)));
+        // The far corner takes longer than the search goes on.
+        assert_eq!(arrival[Board::index(&Point::new(22, 20))], UNREACHABLE);
+        assert!(arrival[Board::index(&Point::new(12, 0))] <= ROUTE_TURNS);
+    }
+
+    #[test]
+    fn barrel_value_is_rum_per_turn() {
+        let ship = Ship::new(0, 5, 10, 0, 1, 90);
+        let barrel = Barrel::new(1, 8, 10, 15);
+        // Only 10 fit in the hold.
+        assert_eq!(barrel_value(&ship, &barrel, 4), 2f64);
+        let full = Ship::new(0, 5, 10, 0, 1, MAX_SHIP_RUM);
+        assert_eq!(barrel_value(&full, &barrel, 0), 1f64);
+    }
+}
@@ -0,0 +1,127 @@
+use std::cmp;
+use std::collections::BTreeMap;
+
+use action::Action;
+use entities::Ship;
+use game::Game;
+use referee::{HIGH_DAMAGE, MAX_SHIP_RUM, REWARD_RUM_BARREL_VALUE};
+
+// How close the teammate has to be before the ship shoots itself.
+const SACRIFICE_RANGE: i32 = 4;
+// Turns between deciding and the teammate picking the barrel up: stopping, the shot, the pickup.
+const SACRIFICE_DELAY: i32 = 4;
+
+impl Game {
+    // A sinking ship drops a barrel with up to 30 of its rum. When `ship` has no barrel left
+    // to heal on and its rum is worth more in a teammate's hold than in its own by the end of
+    // the game, it stops next to the teammate and shoots itself. `committed` holds the orders
+    // our other ships already took this turn.
+    pub fn sacrifice(&self, ship: &Ship, has_barrel: bool, committed: &BTreeMap<i32, Action>) -> Option<Action> {
+        if has_barrel || (ship.rum > HIGH_DAMAGE) {
+            return None;
+        }
+        let turns_left = self.turns_left();
+        let kept = cmp::max(0, ship.rum - turns_left);
+        // Only the poorest ship gives its rum away, to the closest of the others.
+        let mate = self.my_ships.values()
+            .filter(|mate| mate.is_alive(self.current_tick) && (mate.entity_id != ship.entity_id))
+            .filter(|mate| (mate.rum, mate.entity_id) > (ship.rum, ship.entity_id))
+            .min_by_key(|mate| ship.point.distance(&mate.point))?;
+        let distance = ship.point.distance(&mate.point);
+        let transfer = cmp::min(cmp::min(REWARD_RUM_BARREL_VALUE, ship.rum - SACRIFICE_DELAY), MAX_SHIP_RUM - mate.rum);
+        if (transfer <= kept) || (turns_left < distance + SACRIFICE_DELAY) {
+            return None;
+        }
+        // No point dropping the barrel for an enemy.
+        let alive_enemies = self.enemy_ships.values().filter(|enemy_ship| enemy_ship.is_alive(self.current_tick));
+        if alive_enemies.map(|enemy_ship| ship.point.distance(&enemy_ship.point)).any(|d| d <= distance) {
+            return None;
+        }
+        print_err!("SACRIFICE {} to {} ({} rum)", ship.entity_id, mate.entity_id, transfer);
+        if distance > SACRIFICE_RANGE {
+            return Some(self.move_to(&mate.point, &ship.point, ship.rotation, ship.speed, ship.entity_id, committed));
+        }
+        if ship.speed > 0 {
+            return Some(Action::SLOWER);
+        }
+        if (ship.cd == 0) && !self.impact_field.iter().any(|impacts| impacts.contains(&ship.point)) {
+            return Some(Action::FIRE(ship.point.x, ship.point.y));
+        }
+        // No MINE instead while the cannon reloads: the mine lands behind the stern, a ship without
+        // reverse needs a loop of several turns to sail back onto it, and the blast hits the
+        // teammate waiting next to it too.
+        Some(Action::WAIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entities::Ship;
+    use hex::Point;
+    use super::*;
+
+    // Our poor ship 0 next to the rich ship 2 with 60 turns left, the enemy far away.
+    fn game(speed: i32, cd: i32) -> Game {
+        let mut game = Game::default();
+        let mut poor = Ship::new(0, 5, 5, 0, speed, 20);
+        poor.cd = cd;
+        for ship in [poor, Ship::new(2, 5, 7, 0, 0, 60)].iter() {
+            game.my_ships_ids.push(ship.entity_id);
+            game.my_ships.insert(ship.entity_id, ship.clone());
+        }
+        game.enemy_ships.insert(1, Ship::new(1, 20, 18, 3, 0, 100));
+        set_tick(&mut game, 140);
+        game
+    }
+
+    fn set_tick(game: &mut Game, tick: i32) {
+        game.current_tick = tick;
+        for ship in game.my_ships.values_mut().chain(game.enemy_ships.values_mut()) {
+            ship.tick_accessed = tick;
+        }
+    }
+
+    fn decide(game: &Game, ship_id: i32) -> Option<Action> {
+        game.sacrifice(&game.my_ships[&ship_id], false, &BTreeMap::new())
+    }
+
+    #[test]
+    fn stopped_ship_fires_at_itself_next_to_its_mate() {
+        assert_eq!(decide(&game(0, 0), 0), Some(Action::FIRE(5, 5)));
+    }
+
+    #[test]
+    fn moving_ship_stops_first() {
+        assert_eq!(decide(&game(1, 0), 0), Some(Action::SLOWER));
+    }
+
+    #[test]
+    fn reloading_ship_waits_instead_of_mining() {
+        assert_eq!(decide(&game(0, 1), 0), Some(Action::WAIT));
+    }
+
+    #[test]
+    fn richer_ship_or_barrel_left_means_no_sacrifice() {
+        let game = game(0, 0);
+        assert_eq!(decide(&game, 2), None);
+        assert_eq!(game.sacrifice(&game.my_ships[&0], true, &BTreeMap::new()), None);
+    }
+
+    #[test]
+    fn rum_kept_until_the_end_means_no_sacrifice() {
+        // 45 rum with 10 turns left still holds 35 at the end, more than the 30 a barrel takes.
+        let mut game = game(0, 0);
+        set_tick(&mut game, 190);
+        game.my_ships.get_mut(&0).unwrap().rum = 45;
+        assert_eq!(decide(&game, 0), None);
+        game.my_ships.get_mut(&0).unwrap().rum = 35;
+        assert_eq!(decide(&game, 0), Some(Action::FIRE(5, 5)));
+    }
+
+    #[test]
+    fn enemy_closer_than_the_mate_means_no_sacrifice() {
+        let mut game = game(0, 0);
+        game.enemy_ships.get_mut(&1).unwrap().point = Point::new(7, 5);
+        assert_eq!(decide(&game, 0), None);
+    }
+}
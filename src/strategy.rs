@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use action::Action;
+use endgame::Stance;
+use game::Game;
+use phase::Phase;
+use planner::{Planner, PlannerKind};
+use targeting::Shot;
+use timer::DECIDE_MS;
+
+const MIN_HIT_CHANCE: f64 = 0.4;
+const ALL_IN_HIT_CHANCE: f64 = 0.2;
+
+pub struct Decision {
+    pub action: Action,
+    // Patrol waypoint the ship heads for from now on, None keeps the current one.
+    pub waypoint: Option<usize>,
+}
+
+impl Decision {
+    fn new(action: Action) -> Decision {
+        Decision {
+            action: action,
+            waypoint: None,
+        }
+    }
+}
+
+pub trait Strategy {
+    // One decision per alive ship of ours, in `my_ships_ids` order.
+    fn decide(&self, game: &Game) -> Vec<Decision>;
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum StrategyKind {
+    // Firing rules, barrel chasing and waypoint patrol.
+    #[default]
+    Heuristic,
+    // Whatever the planner finds best, firing when it would rather wait.
+    Search,
+}
+
+impl StrategyKind {
+    pub fn parse(name: &str) -> Option<StrategyKind> {
+        match name {
+            "heuristic" => Some(StrategyKind::Heuristic),
+            "search" => Some(StrategyKind::Search),
+            _ => None,
+        }
+    }
+
+    pub fn strategy(self) -> Box<dyn Strategy> {
+        match self {
+            StrategyKind::Heuristic => Box::new(Heuristic),
+            StrategyKind::Search => Box::new(Search),
+        }
+    }
+}
+
+pub struct Heuristic;
+
+impl Strategy for Heuristic {
+    fn decide(&self, game: &Game) -> Vec<Decision> {
+        let mut decisions = Vec::new();
+        print_err!("THREATS fire {:?} mine {:?}", game.fire_threats(), game.mine_threats());
+        let planned = game.planner.plan(game, &game.timer.reserved(DECIDE_MS));
+        let assignment = game.assign();
+        let phase = game.phase();
+        let endgame = game.endgame();
+        print_err!("PHASE {:?} {:?} {} turns left, {} ships against {}", phase, endgame, game.turns_left(),
+                   game.ship_count, game.enemy_ship_count());
+        let min_hit_chance = if (phase == Phase::AllIn) || (endgame == Some(Stance::Attack)) {ALL_IN_HIT_CHANCE} else {MIN_HIT_CHANCE};
+        // Orders already taken this turn, the ships deciding later steer around them.
+        let mut committed = BTreeMap::new();
+        for key in game.my_ships_ids.iter() {
+            let ship = game.my_ships.get(key).unwrap();
+            if !ship.is_alive(game.current_tick) {
+                continue;
+            }
+            let mut action = Action::WAIT;
+            let mut waypoint = None;
+            let barrel_id = assignment.barrels.get(key).cloned().unwrap_or(-1);
+            // Past the deadline the ships only move: no sacrifice, endgame search, mine or shot.
+            let sacrifice = if game.timer.is_over() {None} else {game.sacrifice(ship, barrel_id >= 0, &committed)};
+            if let Some(sacrifice) = sacrifice {
+                action = sacrifice;
+            } else {
+                if (ship.rum > 50) && (action == Action::WAIT) && (ship.cd == 0) {
+                    let enemy_id = game.get_target(ship);
+                    if enemy_id > 0 {
+                        let enemy_ship = game.enemy_ships.get(&enemy_id).unwrap();
+                        action = Action::FIRE(enemy_ship.point.x, enemy_ship.point.y);
+                    }
+                }
+                if (action == Action::WAIT) && (barrel_id >= 0) {
+                    let barel = game.barrels.get(&barrel_id).unwrap();
+                    print_err!("MOVE HEAL {} {}", barel.point.x, barel.point.y);
+                    action = planned.get(key).cloned().unwrap_or_else(||
+                        game.move_to(&barel.point, &ship.point, ship.rotation, ship.speed, ship.entity_id, &committed));
+                } else if let (Action::WAIT, Some(stance), false) = (action, endgame, game.timer.is_over()) {
+                    print_err!("ENDGAME {:?}", stance);
+                    action = game.endgame_move(ship, stance);
+                } else if action == Action::WAIT {
+                    let (p_t, wp_ind) = match phase {
+                        Phase::Lead => game.safe_waypoint(ship),
+                        _ => Game::get_waypoint(ship),
+                    };
+                    waypoint = Some(wp_ind);
+                    print_err!("MOVE AWAY {} {}", p_t.x, p_t.y);
+                    action = planned.get(key).cloned().unwrap_or_else(||
+                        game.move_to(&p_t, &ship.point, ship.rotation, ship.speed, ship.entity_id, &committed));
+                }
+                if (action == Action::WAIT) && !game.timer.is_over() && game.should_mine(ship) {
+                    print_err!("MINE {}", ship.entity_id);
+                    action = Action::MINE;
+                }
+                if (action == Action::WAIT) && (ship.cd == 0) && !game.timer.is_over() {
+                    // The assigned enemy first, any enemy if it cannot be hit.
+                    let likely = |shot: &Shot| shot.hit_chance >= min_hit_chance;
+                    let shot = game.best_shot(ship, assignment.targets.get(key).cloned()).filter(likely)
+                        .or_else(|| game.best_shot(ship, None).filter(likely));
+                    if let Some(shot) = shot {
+                        print_err!("SHOT {} {} {:.2} {:.1}", shot.target.x, shot.target.y, shot.hit_chance, shot.damage);
+                        action = Action::FIRE(shot.target.x, shot.target.y);
+                    }
+                }
+            }
+            committed.insert(*key, action);
+            decisions.push(Decision {action: action, waypoint: waypoint});
+        }
+        decisions
+    }
+}
+
+pub struct Search;
+
+impl Strategy for Search {
+    fn decide(&self, game: &Game) -> Vec<Decision> {
+        // The tree planner only helps the heuristic, searching alone needs a joint one.
+        let planner = match game.planner.kind {
+            PlannerKind::Tree => Planner {kind: PlannerKind::Beam, ..game.planner.clone()},
+            _ => game.planner.clone(),
+        };
+        let planned = planner.plan(game, &game.timer.reserved(DECIDE_MS));
+        if planned.is_empty() {
+            // Out of time before the first move was scored, the rules know better than WAIT.
+            return Heuristic.decide(game);
+        }
+        let mut decisions = Vec::new();
+        for key in game.my_ships_ids.iter() {
+            let ship = game.my_ships.get(key).unwrap();
+            if !ship.is_alive(game.current_tick) {
+                continue;
+            }
+            let mut action = planned.get(key).cloned().unwrap_or(Action::WAIT);
+            if (action == Action::WAIT) && (ship.cd == 0) && !game.timer.is_over() {
+                if let Some(shot) = game.best_shot(ship, None).filter(|shot| shot.hit_chance >= MIN_HIT_CHANCE) {
+                    print_err!("SHOT {} {} {:.2} {:.1}", shot.target.x, shot.target.y, shot.hit_chance, shot.damage);
+                    action = Action::FIRE(shot.target.x, shot.target.y);
+                }
+            }
+            decisions.push(Decision::new(action));
+        }
+        decisions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use entities::Ship;
+    use referee::MAX_SHIP_RUM;
+    use super::*;
+
+    // Our ship sailing full speed to its next waypoint with an enemy in range.
+    fn game(budget_ms: u64) -> Game {
+        let mut game = Game::default();
+        game.my_ships_ids.push(0);
+        game.my_ships.insert(0, Ship::new(0, 4, 3, 0, 2, MAX_SHIP_RUM));
+        game.enemy_ships.insert(1, Ship::new(1, 9, 6, 0, 0, MAX_SHIP_RUM));
+        game.timer.start_at(Instant::now(), Duration::from_millis(budget_ms));
+        game
+    }
+
+    fn actions(kind: StrategyKind, budget_ms: u64) -> Vec<Action> {
+        kind.strategy().decide(&game(budget_ms)).iter().map(|decision| decision.action).collect()
+    }
+
+    #[test]
+    fn ships_fire_when_time_is_left() {
+        assert_eq!(actions(StrategyKind::Heuristic, 1000), vec![Action::FIRE(9, 6)]);
+        assert_eq!(actions(StrategyKind::Search, 1000), vec![Action::FIRE(9, 6)]);
+    }
+
+    #[test]
+    fn late_turn_skips_the_optional_steps() {
+        assert_eq!(actions(StrategyKind::Heuristic, 0), vec![Action::WAIT]);
+        assert_eq!(actions(StrategyKind::Search, 0), vec![Action::WAIT]);
+    }
+}
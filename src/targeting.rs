@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use action::Action;
+use entities::Ship;
+use game::Game;
+use hex::Point;
+use referee::{self, FIRE_DISTANCE_MAX, HIGH_DAMAGE, LOW_DAMAGE};
+
+pub const MOVES: [Action; 5] = [Action::WAIT, Action::FASTER, Action::SLOWER, Action::PORT, Action::STARBOARD];
+
+// Turns of enemy orders enumerated, later turns repeat WAIT.
+const PLANNED_TURNS: usize = 2;
+// Enemy movement turns covered, a shot at the longest range flies through 5 of them.
+const MAX_TURNS: usize = 5;
+
+pub struct Shot {
+    pub target: Point,
+    pub hit_chance: f64,
+    pub damage: f64,
+}
+
+// Possible futures of a ship as (probability, (center, rotation) after 1..=MAX_TURNS turns),
+// `probabilities` weighs each of `MOVES` on the enumerated turns.
+pub fn predict(ship: &Ship, probabilities: &[f64; 5]) -> Vec<(f64, Vec<(Point, i32)>)> {
+    let mut scenarios = vec![(1f64, Vec::new(), ship.point, ship.rotation, ship.speed)];
+    for turn in 0..MAX_TURNS {
+        let mut next = Vec::new();
+        for (p, positions, point, rotation, speed) in scenarios {
+            let moves: &[Action] = if turn < PLANNED_TURNS {&MOVES} else {&MOVES[..1]};
+            for (m, action) in moves.iter().enumerate() {
+                let (n_point, n_rotation, n_speed) = referee::move_alone(point, rotation, speed, *action);
+                let mut n_positions = positions.clone();
+                n_positions.push((n_point, n_rotation));
+                let n_p = if turn < PLANNED_TURNS {p*probabilities[m]} else {p};
+                next.push((n_p, n_positions, n_point, n_rotation, n_speed));
+            }
+        }
+        scenarios = next;
+    }
+    scenarios.into_iter().map(|(p, positions, _, _, _)| (p, positions)).collect()
+}
+
+impl Game {
+    // The tile to shoot that most likely damages an enemy, given the real flight time of
+    // the cannonball from our bow and every way the enemy may move in the meantime. With
+    // `target` set only that enemy is considered.
+    pub fn best_shot(&self, ship: &Ship, target: Option<i32>) -> Option<Shot> {
+        let bow = ship.point.neighbour(ship.rotation);
+        let mut best: Option<Shot> = None;
+        for enemy_ship in self.enemy_ships.values() {
+            if !enemy_ship.is_alive(self.current_tick) || target.is_some_and(|id| id != enemy_ship.entity_id) {
+                continue;
+            }
+            let scenarios = predict(enemy_ship, &self.opponent.move_probabilities(enemy_ship.entity_id));
+            let mut candidates: HashMap<Point, (f64, f64)> = HashMap::new();
+            for (_, positions) in scenarios.iter() {
+                for &(point, rotation) in positions.iter() {
+                    for tile in [point, point.neighbour(rotation), point.neighbour((rotation + 3)%6)].iter() {
+                        if tile.is_inside() && (bow.distance(tile) <= FIRE_DISTANCE_MAX) {
+                            candidates.insert(*tile, (0f64, 0f64));
+                        }
+                    }
+                }
+            }
+            for (tile, value) in candidates.iter_mut() {
+                // The enemy moves on the turn we fire and on every turn of the flight.
+                let turn = referee::travel_time(bow.distance(tile)) as usize;
+                for &(p, ref positions) in scenarios.iter() {
+                    let (point, rotation) = positions[turn];
+                    if point == *tile {
+                        value.0 += p;
+                        value.1 += p*(HIGH_DAMAGE as f64);
+                    } else if (point.neighbour(rotation) == *tile) || (point.neighbour((rotation + 3)%6) == *tile) {
+                        value.0 += p;
+                        value.1 += p*(LOW_DAMAGE as f64);
+                    }
+                }
+            }
+            for (tile, &(hit_chance, damage)) in candidates.iter() {
+                let better = match best {
+                    Some(ref shot) => (damage > shot.damage) ||
+                        ((damage == shot.damage) && ((tile.y, tile.x) < (shot.target.y, shot.target.x))),
+                    None => true,
+                };
+                if better {
+                    best = Some(Shot {target: *tile, hit_chance: hit_chance, damage: damage});
+                }
+            }
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use referee::MAX_SHIP_RUM;
+    use super::*;
+
+    const STRAIGHT: [f64; 5] = [1f64, 0f64, 0f64, 0f64, 0f64];
+
+    #[test]
+    fn predict_covers_every_planned_order() {
+        let ship = Ship::new(1, 10, 10, 0, 1, MAX_SHIP_RUM);
+        let scenarios = predict(&ship, &[0.2; 5]);
+        assert_eq!(scenarios.len(), MOVES.len()*MOVES.len());
+        assert!((scenarios.iter().map(|&(p, _)| p).sum::<f64>() - 1f64).abs() < 1e-9);
+        assert!(scenarios.iter().all(|(_, positions)| positions.len() == MAX_TURNS));
+    }
+
+    #[test]
+    fn predict_follows_a_straight_course() {
+        let ship = Ship::new(1, 10, 10, 0, 1, MAX_SHIP_RUM);
+        let (p, ref positions) = predict(&ship, &STRAIGHT)[0];
+        assert_eq!(p, 1f64);
+        for (turn, &position) in positions.iter().enumerate() {
+            assert_eq!(position, (Point::new(11 + turn as i32, 10), 0));
+        }
+    }
+
+    #[test]
+    fn straight_moving_enemy_is_hit_where_it_arrives() {
+        let mut game = Game::default();
+        let mine = Ship::new(1, 5, 10, 0, 0, MAX_SHIP_RUM);
+        let enemy = Ship::new(2, 8, 14, 0, 1, MAX_SHIP_RUM);
+        game.my_ships_ids.push(1);
+        game.my_ships.insert(1, mine.clone());
+        game.enemy_ships.insert(2, enemy.clone());
+        for _ in 0..1000 {
+            game.opponent.observe(2, Action::WAIT);
+        }
+        let shot = game.best_shot(&mine, Some(2)).unwrap();
+        let turn = referee::travel_time(mine.point.neighbour(0).distance(&shot.target)) as usize;
+        let positions = &predict(&enemy, &STRAIGHT)[0].1;
+        assert_eq!(shot.target, positions[turn].0);
+        assert!(shot.hit_chance > 0.9);
+        assert!(game.best_shot(&mine, Some(3)).is_none());
+    }
+}
@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+// CodinGame limits: 1s for the first answer, 50ms for the others.
+pub const FIRST_TURN_MS: u64 = 1000;
+pub const TURN_MS: u64 = 50;
+// Kept free for printing the answer and process scheduling noise.
+const SAFETY_MS: u64 = 10;
+// Kept free by the planners for the rules deciding after them: sacrifice, mining and targeting.
+pub const DECIDE_MS: u64 = 8;
+
+#[derive(Default, Clone)]
+pub struct Timer {
+    start: Option<Instant>,
+    budget: Duration,
+}
+
+impl Timer {
+    pub fn turn_budget(current_tick: i32) -> Duration {
+        Duration::from_millis(if current_tick == 0 {FIRST_TURN_MS} else {TURN_MS})
+    }
+
+    pub fn start_at(&mut self, start: Instant, budget: Duration) {
+        self.start = Some(start);
+        self.budget = budget;
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.map(|start| start.elapsed()).unwrap_or_default()
+    }
+
+    // The moment searches have to give up, the safety margin already taken off.
+    pub fn deadline(&self) -> Instant {
+        let start = self.start.unwrap_or_else(Instant::now);
+        start + self.budget.saturating_sub(Duration::from_millis(SAFETY_MS))
+    }
+
+    // The same turn with `ms` less to spend, for a search that has to leave time to the rest.
+    pub fn reserved(&self, ms: u64) -> Timer {
+        Timer {
+            start: self.start,
+            budget: self.budget.saturating_sub(Duration::from_millis(ms)),
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        Instant::now() >= self.deadline()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_timer_ends_earlier() {
+        let mut timer = Timer::default();
+        let start = Instant::now();
+        timer.start_at(start, Duration::from_millis(TURN_MS));
+        let search = timer.reserved(DECIDE_MS);
+        assert_eq!(timer.deadline() - search.deadline(), Duration::from_millis(DECIDE_MS));
+        assert_eq!(timer.reserved(2*TURN_MS).deadline(), start);
+        assert!(timer.reserved(2*TURN_MS).is_over());
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use board::{Bitboard, Board, TILES};
+use entities::Ship;
+use game::Game;
+use referee::{COOLDOWN_CANNON, COOLDOWN_MINE, MINE_VISIBILITY_RANGE};
+
+// Who fired every cannonball and dropped every mine seen so far, -1 when it cannot be told
+// (a mine that was already out of sight when it showed up, or one of ours).
+#[derive(Default, Clone)]
+pub struct Tracker {
+    pub cannonball_owners: BTreeMap<i32, i32>,
+    pub mine_owners: BTreeMap<i32, i32>,
+    // Enemy ships that fired or mined on the last turn.
+    pub fired: Vec<i32>,
+    pub mined: Vec<i32>,
+    // Tiles where our ships saw mines on the last turn.
+    pub visible: Bitboard,
+}
+
+impl Game {
+    // Matches the cannonballs and mines that showed up this turn to the enemy ships, as they
+    // were on the previous turn, and restarts the cooldowns of those ships.
+    pub fn track_enemies(&mut self, prev_enemy_ships: &BTreeMap<i32, Ship>) {
+        self.tracker.fired.clear();
+        self.tracker.mined.clear();
+        let prev_tick = self.current_tick - 1;
+        for cannonball in self.cannonballs.values() {
+            if self.tracker.cannonball_owners.contains_key(&cannonball.entity_id) {
+                continue;
+            }
+            self.tracker.cannonball_owners.insert(cannonball.entity_id, cannonball.owner_id);
+            if let Some(enemy_ship) = self.enemy_ships.get_mut(&cannonball.owner_id) {
+                // The referee already counted down the turn of the shot.
+                enemy_ship.set_cd(COOLDOWN_CANNON - 1);
+                self.tracker.fired.push(cannonball.owner_id);
+            }
+        }
+        for mine in self.mines.values() {
+            if self.tracker.mine_owners.contains_key(&mine.entity_id) {
+                continue;
+            }
+            // A mine on a tile we could not see may be older than the last turn.
+            if !self.tracker.visible.contains(&mine.point) {
+                self.tracker.mine_owners.insert(mine.entity_id, -1);
+                continue;
+            }
+            let owner = prev_enemy_ships.values()
+                .find(|prev| prev.is_alive(prev_tick) && (prev.mine_cd == 0) && (Game::mine_target(prev) == mine.point))
+                .map(|prev| prev.entity_id)
+                .unwrap_or(-1);
+            self.tracker.mine_owners.insert(mine.entity_id, owner);
+            if let Some(enemy_ship) = self.enemy_ships.get_mut(&owner) {
+                enemy_ship.set_mine_cd(COOLDOWN_MINE - 1);
+                self.tracker.mined.push(owner);
+            }
+        }
+        self.tracker.visible = self.visible_tiles();
+    }
+
+    // Tiles close enough to one of our ships for the mines on them to be in the input.
+    fn visible_tiles(&self) -> Bitboard {
+        let board = Board::get();
+        let ships: Vec<usize> = self.my_ships.values()
+            .filter(|ship| ship.is_alive(self.current_tick) && ship.point.is_inside())
+            .map(|ship| Board::index(&ship.point))
+            .collect();
+        let mut visible = Bitboard::default();
+        for tile in 0..TILES {
+            if ships.iter().any(|&ship| board.distance(ship, tile) <= MINE_VISIBILITY_RANGE) {
+                visible.insert(&Board::point(tile));
+            }
+        }
+        visible
+    }
+
+    // Enemy ships able to FIRE on the next turn.
+    pub fn fire_threats(&self) -> Vec<i32> {
+        self.enemy_ships.values()
+            .filter(|enemy_ship| enemy_ship.is_alive(self.current_tick) && (enemy_ship.cd == 0))
+            .map(|enemy_ship| enemy_ship.entity_id)
+            .collect()
+    }
+
+    // Enemy ships able to MINE on the next turn.
+    pub fn mine_threats(&self) -> Vec<i32> {
+        self.enemy_ships.values()
+            .filter(|enemy_ship| enemy_ship.is_alive(self.current_tick) && (enemy_ship.mine_cd == 0))
+            .map(|enemy_ship| enemy_ship.entity_id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use entities::Mine;
+    use hex::Point;
+    use referee::MAX_SHIP_RUM;
+    use super::*;
+
+    // Turn 1 after an enemy able to mine sailed east from (10, 10), our ship sailing east from `x`.
+    fn game(x: i32) -> (Game, BTreeMap<i32, Ship>) {
+        let mut game = Game::default();
+        game.my_ships_ids.push(0);
+        game.my_ships.insert(0, Ship::new(0, x, 10, 0, 1, MAX_SHIP_RUM));
+        let enemy = Ship::new(1, 10, 10, 0, 1, MAX_SHIP_RUM);
+        game.tracker.visible = game.visible_tiles();
+        let mut prev_enemy_ships = BTreeMap::new();
+        prev_enemy_ships.insert(1, enemy.clone());
+        game.current_tick = 1;
+        game.my_ships.get_mut(&0).unwrap().update(1, x + 1, 10, 0, 1, MAX_SHIP_RUM);
+        let mut moved = enemy;
+        moved.update(1, 11, 10, 0, 1, MAX_SHIP_RUM);
+        game.enemy_ships.insert(1, moved);
+        (game, prev_enemy_ships)
+    }
+
+    fn drop_mine(game: &mut Game, prev_enemy_ships: &BTreeMap<i32, Ship>) {
+        let target = Game::mine_target(&prev_enemy_ships[&1]);
+        assert_eq!(target, Point::new(8, 10));
+        let mut mine = Mine::new(5, target.x, target.y);
+        mine.keep_alive(1);
+        game.mines.insert(5, mine);
+        game.track_enemies(prev_enemy_ships);
+    }
+
+    #[test]
+    fn mine_dropped_in_sight_has_an_owner() {
+        let (mut game, prev_enemy_ships) = game(5);
+        drop_mine(&mut game, &prev_enemy_ships);
+        assert_eq!(game.tracker.mine_owners[&5], 1);
+        assert_eq!(game.tracker.mined, vec![1]);
+        assert_eq!(game.enemy_ships[&1].mine_cd, COOLDOWN_MINE - 1);
+    }
+
+    #[test]
+    fn mine_coming_into_view_has_no_owner() {
+        // Our ship was 6 tiles away, one more than the mines are shown at, and is now 5.
+        let (mut game, prev_enemy_ships) = game(2);
+        drop_mine(&mut game, &prev_enemy_ships);
+        assert_eq!(game.tracker.mine_owners[&5], -1);
+        assert!(game.tracker.mined.is_empty());
+        assert_eq!(game.enemy_ships[&1].mine_cd, 0);
+    }
+}